@@ -1,87 +1,337 @@
+use std::fmt;
+
 use crate::utils;
+use crate::utils::args::Args;
+
+/// Rotations that could not be parsed
+#[derive(Debug)]
+enum InputError {
+    UnknownDirection { line: usize, text: String },
+    MalformedCount { line: usize, text: String },
+    RotationTooLarge { line: usize, text: String },
+}
+
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InputError::UnknownDirection { line, text } => {
+                write!(
+                    f,
+                    "line {line}: rotation should start with L or R in '{text}'"
+                )
+            }
+            InputError::MalformedCount { line, text } => {
+                write!(f, "line {line}: cannot parse click count from '{text}'")
+            }
+            InputError::RotationTooLarge { line, text } => {
+                write!(f, "line {line}: rotation does not fit in i32 in '{text}'")
+            }
+        }
+    }
+}
 
-fn parse_line(line: String) -> i32 {
-    let mut chars = line.chars();
-    let direction = chars.next().expect("should find first character");
-    let count = chars
-        .as_str()
-        .parse::<i32>()
-        .expect("should parse string into int");
-    if direction == 'R' { count } else { -count }
+/// Parse a rotation like `L68` into a signed click count, negative for left
+fn parse_line(line_number: usize, line: &str) -> Result<i32, InputError> {
+    let text = line.trim();
+
+    let (sign, count) = if let Some(count) = text.strip_prefix('R') {
+        (1, count)
+    } else if let Some(count) = text.strip_prefix('L') {
+        (-1, count)
+    } else {
+        return Err(InputError::UnknownDirection {
+            line: line_number,
+            text: text.to_string(),
+        });
+    };
+
+    // only plain digits, str::parse would also accept a sign like `R+5` or `L-5`
+    if count.is_empty() || !count.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(InputError::MalformedCount {
+            line: line_number,
+            text: text.to_string(),
+        });
+    }
+    // both directions are kept within i32::MAX, so negating a count can never overflow
+    let count: i32 = count.parse().map_err(|_| InputError::RotationTooLarge {
+        line: line_number,
+        text: text.to_string(),
+    })?;
+    Ok(sign * count)
 }
 
-fn count_zero_passes(start: i32, rotation: i32) -> i32 {
-    if !(0..100).contains(&start) {
+/// Parse every non-blank line, paired with its line number
+fn parse_rotations(lines: &[String]) -> Result<Vec<(usize, i32)>, InputError> {
+    lines
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| Ok((i + 1, parse_line(i + 1, line)?)))
+        .collect()
+}
+
+/// Count how often mark 0 is passed or landed on when turning a dial of `size` marks
+/// from `start` by `rotation` clicks, right for positive and left for negative rotations
+fn count_zero_passes(size: i32, start: i32, rotation: i32) -> u32 {
+    if !(0..size).contains(&start) {
         panic!("Start has incorrect value: {start}");
     }
 
     let mut zero_passes = 0;
 
-    // for any absolute rotation 100 or greater, there is always a number of zero passes regardless of end position
+    // for any absolute rotation of a full turn or more, there is always a number of zero passes regardless of end position
     // in rust, integer division rounds towards zero (truncating division)
-    zero_passes += (rotation / 100).abs();
+    zero_passes += (rotation / size).unsigned_abs();
 
     // then some analysis remains
-    let rot_remainder = rotation % 100; // the remainder can be negative but is between -100 and 100 exclusive
-    let new_position = start + rot_remainder;
-    if new_position >= 100 {
+    let rot_remainder = rotation % size; // the remainder can be negative but is between -size and size exclusive
+    // compared against the distance to zero instead of adding to start, so a large dial cannot overflow
+    if rot_remainder >= size - start {
         zero_passes += 1
     }; // zero pass while turning right
-    if start != 0 && new_position <= 0 {
+    if start != 0 && rot_remainder <= -start {
         zero_passes += 1
     } // zero pass while turning left
 
     zero_passes
 }
 
-fn part1(lines: &[String]) -> i32 {
-    let mut position = 50;
-    let mut zero_counter = 0;
-    for line in lines {
-        let rotation = parse_line(line.to_string());
-        position = (position + rotation).rem_euclid(100);
-        if position == 0 {
-            zero_counter += 1
+/// A dial with marks `0..size`, turning right counts up and turning left counts down
+#[derive(Clone, Copy, Debug)]
+struct Dial {
+    size: i32,
+    position: i32,
+    target: i32,
+}
+
+/// The dial after a single rotation
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Step {
+    position: i32,
+    /// Times the target mark was passed or landed on during the rotation
+    target_passes: u32,
+}
+
+impl Dial {
+    /// The dial from the puzzle: 100 marks, starting at 50 and counting mark 0
+    const PUZZLE: Dial = Dial {
+        size: 100,
+        position: 50,
+        target: 0,
+    };
+
+    fn new(size: i32, start: i32, target: i32) -> Self {
+        assert!(size > 0, "a dial needs at least one mark");
+        assert!((0..size).contains(&start), "start is not on the dial");
+        assert!((0..size).contains(&target), "target is not on the dial");
+        Dial {
+            size,
+            position: start,
+            target,
+        }
+    }
+
+    fn rotate(&mut self, rotation: i32) -> Step {
+        // passing the target is passing zero on a dial relabelled so the target is mark 0
+        let offset = (self.position - self.target).rem_euclid(self.size);
+        let target_passes = count_zero_passes(self.size, offset, rotation);
+
+        // in i64, so position plus rotation cannot overflow
+        let position = (self.position as i64 + rotation as i64).rem_euclid(self.size as i64);
+        self.position = position as i32;
+
+        Step {
+            position: self.position,
+            target_passes,
+        }
+    }
+
+    /// Apply the rotations one by one, yielding the dial after each of them
+    fn simulate(mut self, rotations: impl IntoIterator<Item = i32>) -> impl Iterator<Item = Step> {
+        rotations
+            .into_iter()
+            .map(move |rotation| self.rotate(rotation))
+    }
+}
+
+fn part1(lines: &[String]) -> Result<usize, InputError> {
+    let dial = Dial::PUZZLE;
+    let rotations = parse_rotations(lines)?.into_iter().map(|(_, r)| r);
+    Ok(dial
+        .simulate(rotations)
+        .filter(|step| step.position == dial.target)
+        .count())
+}
+
+fn part2(lines: &[String]) -> Result<u64, InputError> {
+    let rotations = parse_rotations(lines)?.into_iter().map(|(_, r)| r);
+    Ok(Dial::PUZZLE
+        .simulate(rotations)
+        .map(|step| step.target_passes as u64)
+        .sum())
+}
+
+/// Print the dial before and after every rotation of the input, usage:
+/// `day01 --trace [--csv] [--input=input/day01.txt]`
+/// With `--csv` the trace is written as comma separated values with a header row
+fn run_trace(args: &Args) {
+    let input_path = args.value("input").unwrap_or("input/day01.txt");
+    let csv = args.flag("csv");
+    let lines = utils::read_lines(input_path);
+    let rotations = parse_rotations(&lines).unwrap_or_else(|e| panic!("Invalid day 1 input: {e}"));
+
+    if csv {
+        println!("line,start,rotation,end,zero_passes");
+    }
+    let mut dial = Dial::PUZZLE;
+    let mut total_passes = 0u64;
+    for (line_number, rotation) in rotations {
+        let start = dial.position;
+        let step = dial.rotate(rotation);
+        total_passes += step.target_passes as u64;
+
+        if csv {
+            println!(
+                "{line_number},{start},{rotation},{},{}",
+                step.position, step.target_passes
+            );
+        } else {
+            let turn = if rotation < 0 { 'L' } else { 'R' };
+            println!(
+                "line {line_number:>4}: {start:>2} {turn}{:<4} -> {:>2}, zero passed {} time(s)",
+                rotation.unsigned_abs(),
+                step.position,
+                step.target_passes
+            );
         }
     }
-    zero_counter
+
+    if !csv {
+        println!("Zero passed {total_passes} time(s) in total");
+    }
 }
 
-fn part2(lines: &[String]) -> i32 {
-    let mut position = 50;
-    let mut zero_counter = 0;
-    for line in lines {
-        let rotation = parse_line(line.to_string());
-        zero_counter += count_zero_passes(position, rotation);
-        position = (position + rotation).rem_euclid(100);
+pub fn cli(args: &[String]) {
+    let args = Args::parse(args);
+    match args.positional(0) {
+        None if args.flag("trace") => run_trace(&args),
+        None => main(),
+        Some(command) => panic!("Unknown day 1 command: {command}"),
     }
-    zero_counter
 }
 
 pub fn main() {
     let test_lines = utils::read_lines("input_test/day01.txt");
     let input_lines = utils::read_lines("input/day01.txt");
 
+    assert_eq!(
+        parse_rotations(&utils::lines_from(&[" R5 ", "", "L2147483647", "  "])).unwrap(),
+        vec![(1, 5), (3, -2147483647)]
+    );
+    assert!(matches!(
+        parse_rotations(&utils::lines_from(&["R5", "X5"])),
+        Err(InputError::UnknownDirection { line: 2, .. })
+    ));
+    for malformed in ["L", "R+5", "L-5", "R5x", "R 5"] {
+        assert!(matches!(
+            parse_line(7, malformed),
+            Err(InputError::MalformedCount { line: 7, .. })
+        ));
+    }
+    assert!(matches!(
+        parse_line(3, "L2147483648"),
+        Err(InputError::RotationTooLarge { line: 3, .. })
+    ));
+
     // part 1
-    assert_eq!(part1(&test_lines), 3);
-    println!("Day 1 part 1 answer: {}", part1(&input_lines));
+    assert_eq!(part1(&test_lines).unwrap(), 3);
+    println!("Day 1 part 1 answer: {}", part1(&input_lines).unwrap());
 
     // part 2
-    assert_eq!(count_zero_passes(50, 1), 0);
-    assert_eq!(count_zero_passes(50, 80), 1);
-    assert_eq!(count_zero_passes(50, 180), 2);
-    assert_eq!(count_zero_passes(50, -50), 1);
-    assert_eq!(count_zero_passes(50, 50), 1);
-    assert_eq!(count_zero_passes(50, -1), 0);
-    assert_eq!(count_zero_passes(50, -80), 1);
-    assert_eq!(count_zero_passes(50, -180), 2);
-    assert_eq!(count_zero_passes(0, 50), 0);
-    assert_eq!(count_zero_passes(0, 100), 1);
-    assert_eq!(count_zero_passes(0, 150), 1);
-    assert_eq!(count_zero_passes(0, -50), 0);
-    assert_eq!(count_zero_passes(0, -100), 1);
-    assert_eq!(count_zero_passes(0, -150), 1);
-
-    assert_eq!(part2(&test_lines), 6);
-    println!("Day 1 part 2 answer: {}", part2(&input_lines));
+    assert_eq!(count_zero_passes(100, 50, 1), 0);
+    assert_eq!(count_zero_passes(100, 50, 80), 1);
+    assert_eq!(count_zero_passes(100, 50, 180), 2);
+    assert_eq!(count_zero_passes(100, 50, -50), 1);
+    assert_eq!(count_zero_passes(100, 50, 50), 1);
+    assert_eq!(count_zero_passes(100, 50, -1), 0);
+    assert_eq!(count_zero_passes(100, 50, -80), 1);
+    assert_eq!(count_zero_passes(100, 50, -180), 2);
+    assert_eq!(count_zero_passes(100, 0, 50), 0);
+    assert_eq!(count_zero_passes(100, 0, 100), 1);
+    assert_eq!(count_zero_passes(100, 0, 150), 1);
+    assert_eq!(count_zero_passes(100, 0, -50), 0);
+    assert_eq!(count_zero_passes(100, 0, -100), 1);
+    assert_eq!(count_zero_passes(100, 0, -150), 1);
+
+    // a small dial counting a mark other than zero
+    let steps: Vec<Step> = Dial::new(10, 3, 7).simulate([4, -20, 5, -6]).collect();
+    let expected = [(7, 1), (7, 2), (2, 0), (6, 1)].map(|(position, target_passes)| Step {
+        position,
+        target_passes,
+    });
+    assert_eq!(steps, expected);
+
+    assert_eq!(part2(&test_lines).unwrap(), 6);
+    println!("Day 1 part 2 answer: {}", part2(&input_lines).unwrap());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    /// Reference for `count_zero_passes` that turns the dial one click at a time
+    fn brute_force_zero_passes(size: i32, start: i32, rotation: i32) -> u32 {
+        let step = rotation.signum();
+        let mut position = start;
+        let mut zero_passes = 0;
+        for _ in 0..rotation.unsigned_abs() {
+            position = (position + step).rem_euclid(size);
+            if position == 0 {
+                zero_passes += 1;
+            }
+        }
+        zero_passes
+    }
+
+    /// Reference for rotations too large to click through: count the multiples of `size`
+    /// between start (exclusive) and end (inclusive), using floor division in i64
+    fn floor_div_zero_passes(size: i32, start: i32, rotation: i32) -> u32 {
+        let (size, start) = (size as i64, start as i64);
+        let end = start + rotation as i64;
+        let passes = if rotation >= 0 {
+            end.div_euclid(size) - start.div_euclid(size)
+        } else {
+            (start - 1).div_euclid(size) - (end - 1).div_euclid(size)
+        };
+        passes as u32
+    }
+
+    /// A dial size with a start position on it
+    fn dial(sizes: impl Strategy<Value = i32>) -> impl Strategy<Value = (i32, i32)> {
+        sizes.prop_flat_map(|size| (Just(size), prop_oneof![Just(0), Just(size - 1), 0..size]))
+    }
+
+    proptest! {
+        #[test]
+        fn matches_brute_force((size, start) in dial(1..=25), rotation in -1000..=1000) {
+            let expected = brute_force_zero_passes(size, start, rotation);
+            prop_assert_eq!(count_zero_passes(size, start, rotation), expected);
+            prop_assert_eq!(floor_div_zero_passes(size, start, rotation), expected);
+        }
+
+        #[test]
+        fn matches_floor_division_at_extremes(
+            (size, start) in dial(prop_oneof![Just(1), Just(2), Just(i32::MAX), 1..=i32::MAX]),
+            rotation in prop_oneof![Just(i32::MIN), Just(i32::MAX), Just(0), any::<i32>()],
+        ) {
+            let expected = floor_div_zero_passes(size, start, rotation);
+            prop_assert_eq!(count_zero_passes(size, start, rotation), expected);
+
+            let step = Dial::new(size, start, 0).rotate(rotation);
+            let position = (start as i64 + rotation as i64).rem_euclid(size as i64);
+            prop_assert_eq!(step.position as i64, position);
+            prop_assert_eq!(step.target_passes, expected);
+        }
+    }
 }
@@ -1,85 +1,170 @@
+use std::collections::BTreeSet;
 use std::fs;
 
-fn parse_line(line: &str) -> Vec<(i64, i64)> {
-    let mut ranges: Vec<(i64, i64)> = Vec::new();
+use crate::utils::args::Args;
 
-    for range_str in line.split(",") {
+/// Ids are parsed as u128, which holds up to 38 digits without overflowing
+fn parse_line(line: &str) -> Vec<(u128, u128)> {
+    let mut ranges: Vec<(u128, u128)> = Vec::new();
+
+    for range_str in line.trim().split(",") {
         let bla: Vec<&str> = range_str.split("-").collect();
-        let start = bla[0].parse::<i64>().unwrap();
-        let end = bla[1].parse::<i64>().unwrap();
-        ranges.push((start, end));
+        let parse = |s: &str| {
+            s.trim()
+                .parse::<u128>()
+                .unwrap_or_else(|_| panic!("cannot parse id '{s}' in range '{range_str}'"))
+        };
+        ranges.push((parse(bla[0]), parse(bla[1])));
     }
 
     ranges
 }
 
-/// check if the number is made of a sequence repeated twice
-/// uses a combination of integer division and modulus to split the number in half
-fn is_invalid(num: i64) -> bool {
-    let length = num.to_string().len();
-    if length % 2 == 1 {
-        return false;
-    } // odd numbers never are a sequence repeated twice
-    let base: i64 = 10;
-    let divisor = base.pow((length / 2).try_into().unwrap());
-    let left = num / divisor;
-    let right = num % divisor;
-    left == right
+/// Rule for which ids are invalid: a block of digits in `base`, repeated
+/// between `min_repeats` and `max_repeats` times (inclusive)
+#[derive(Clone, Copy, Debug)]
+struct RepeatRule {
+    min_repeats: u32,
+    max_repeats: u32,
+    base: u128,
 }
 
-fn part1(line: &str) -> i64 {
-    let mut answer = 0;
-    let ranges = parse_line(line);
+impl RepeatRule {
+    /// Part 1: a sequence repeated exactly twice
+    const TWICE: RepeatRule = RepeatRule {
+        min_repeats: 2,
+        max_repeats: 2,
+        base: 10,
+    };
 
-    for (start, end) in ranges {
-        answer += (start..=end).filter(|x| is_invalid(*x)).sum::<i64>()
+    /// Part 2: a sequence repeated at least twice
+    const AT_LEAST_TWICE: RepeatRule = RepeatRule {
+        min_repeats: 2,
+        max_repeats: u32::MAX,
+        base: 10,
+    };
+
+    fn new(min_repeats: u32, max_repeats: u32, base: u128) -> Self {
+        assert!(min_repeats >= 2, "a block has to repeat at least twice");
+        assert!(
+            min_repeats <= max_repeats,
+            "min repeats is larger than max repeats"
+        );
+        assert!(base >= 2, "base should be at least 2");
+        RepeatRule {
+            min_repeats,
+            max_repeats,
+            base,
+        }
     }
 
-    answer
-}
+    fn num_digits(&self, n: u128) -> u32 {
+        n.checked_ilog(self.base).unwrap_or(0) + 1
+    }
 
-/// extract a block of m digits starting at position p (0-indexed from right)
-fn extract_block(n: i64, p: u32, m: u32) -> i64 {
-    let pow_p = 10i64.pow(p);
-    let pow_m = 10i64.pow(m);
-    (n / pow_p) % pow_m
-}
+    /// Block sizes that split a number of this length into an allowed number of equal blocks
+    /// for example 5 digits can never be split into blocks of 2
+    fn block_sizes(&self, length: u32) -> impl Iterator<Item = u32> {
+        (1..=length / 2).filter(move |&block_size| {
+            let repeats = length / block_size;
+            length.is_multiple_of(block_size)
+                && repeats >= self.min_repeats
+                && repeats <= self.max_repeats
+        })
+    }
+
+    /// extract a block of m digits starting at position p (0-indexed from right)
+    fn extract_block(&self, n: u128, p: u32, m: u32) -> u128 {
+        let pow_p = self.base.pow(p);
+        let pow_m = self.base.pow(m);
+        (n / pow_p) % pow_m
+    }
+
+    /// check if the number is made of a single block repeated an allowed number of times
+    /// uses integer division and modulus to compare the blocks, no string conversion needed
+    fn is_invalid(&self, num: u128) -> bool {
+        let length = self.num_digits(num);
+        self.block_sizes(length).any(|block_size| {
+            let first_block = self.extract_block(num, 0, block_size);
+            (1..length / block_size)
+                .all(|i| self.extract_block(num, i * block_size, block_size) == first_block)
+        })
+    }
 
-fn is_invalid2(num: i64) -> bool {
-    // use the new extract_block function!
-    let length = num.to_string().len();
-    for block_size in 1..=(length / 2) {
-        if !length.is_multiple_of(block_size) {
-            continue;
-        } // for example 5 can never be invalid in segments of 2
-        let mut all_equal = true;
-        let num_blocks = length / block_size;
-        let first_block = extract_block(num, 0, block_size as u32);
-        for block_index in 1..num_blocks {
-            let current_block =
-                extract_block(num, (block_index * block_size) as u32, block_size as u32);
-            if current_block != first_block {
-                all_equal = false;
-                break;
+    /// Generate every invalid id in [start, end], by multiplying candidate blocks with a repunit
+    /// like 1001001 (a 3-digit block repeated 3 times) instead of checking every number
+    /// The same id can be built from several block sizes (111111 is 111 twice or 11 three times),
+    /// collecting into a set removes those duplicates
+    fn invalid_ids(&self, start: u128, end: u128) -> BTreeSet<u128> {
+        let base = self.base;
+        let mut ids = BTreeSet::new();
+
+        for length in self.num_digits(start)..=self.num_digits(end) {
+            // part of the range with exactly this many digits, the upper bound can exceed u128
+            let low = start.max(base.pow(length - 1));
+            let high = end.min(base.checked_pow(length).map_or(u128::MAX, |p| p - 1));
+
+            for block_size in self.block_sizes(length) {
+                // a repunit that does not fit in u128 is larger than any id
+                let Some(repunit) = (0..length / block_size).try_fold(0u128, |acc, i| {
+                    acc.checked_add(base.checked_pow(i * block_size)?)
+                }) else {
+                    continue;
+                };
+
+                let low_block = low.div_ceil(repunit);
+                let first_block = base.pow(block_size - 1).max(low_block);
+                let last_block = (base.pow(block_size) - 1).min(high / repunit);
+                ids.extend((first_block..=last_block).map(|block| block * repunit));
             }
         }
-        if all_equal {
-            return true;
-        }
-    }
 
-    false // default return false if no block size matched
+        ids
+    }
 }
 
-fn part2(line: &str) -> i64 {
-    let mut answer = 0;
+/// Sum of all invalid ids, panics instead of wrapping if the sum does not fit in u128
+fn sum_invalid(line: &str, rule: &RepeatRule) -> u128 {
     let ranges = parse_line(line);
+    ranges
+        .iter()
+        .flat_map(|&(start, end)| rule.invalid_ids(start, end))
+        .try_fold(0u128, |acc, id| acc.checked_add(id))
+        .expect("sum of invalid ids should fit in u128")
+}
 
-    for (start, end) in ranges {
-        answer += (start..=end).filter(|x| is_invalid2(*x)).sum::<i64>()
-    }
+fn part1(line: &str) -> u128 {
+    sum_invalid(line, &RepeatRule::TWICE)
+}
 
-    answer
+fn part2(line: &str) -> u128 {
+    sum_invalid(line, &RepeatRule::AT_LEAST_TWICE)
+}
+
+/// Sum the invalid ids under a custom rule, usage:
+/// `day02 rule [--input=input/day02.txt] [--min-repeats=2] [--max-repeats=N] [--base=10]`
+fn run_rule(args: &Args) {
+    let input_path = args.value("input").unwrap_or("input/day02.txt");
+    let rule = RepeatRule::new(
+        args.parsed_or("min-repeats", 2),
+        args.parsed_or("max-repeats", u32::MAX),
+        args.parsed_or("base", 10),
+    );
+
+    let line = fs::read_to_string(input_path).expect("should be able to read file");
+    println!(
+        "Sum of invalid ids for {rule:?}: {}",
+        sum_invalid(&line, &rule)
+    );
+}
+
+pub fn cli(args: &[String]) {
+    let args = Args::parse(args);
+    match args.positional(0) {
+        None => main(),
+        Some("rule") => run_rule(&args),
+        Some(command) => panic!("Unknown day 2 command: {command}"),
+    }
 }
 
 pub fn main() {
@@ -87,25 +172,112 @@ pub fn main() {
         fs::read_to_string("input_test/day02.txt").expect("should be able to read file");
     let input_line = fs::read_to_string("input/day02.txt").expect("should be able to read file");
 
-    assert!(is_invalid(11));
-    assert!(is_invalid(1010));
-    assert!(is_invalid(446446));
+    let twice = RepeatRule::TWICE;
+    assert!(twice.is_invalid(11));
+    assert!(twice.is_invalid(1010));
+    assert!(twice.is_invalid(446446));
 
-    assert!(!is_invalid(12));
-    assert!(!is_invalid(123));
-    assert!(!is_invalid(700234098));
+    assert!(!twice.is_invalid(12));
+    assert!(!twice.is_invalid(123));
+    assert!(!twice.is_invalid(700234098));
 
     assert_eq!(part1(&test_line), 1227775554);
     println!("Day 2 part 1 answer: {}", part1(&input_line));
 
-    assert!(is_invalid2(111));
-    assert!(is_invalid2(1010));
-    assert!(is_invalid2(824824824));
+    let at_least_twice = RepeatRule::AT_LEAST_TWICE;
+    assert!(at_least_twice.is_invalid(111));
+    assert!(at_least_twice.is_invalid(1010));
+    assert!(at_least_twice.is_invalid(824824824));
 
-    assert!(!is_invalid2(10));
-    assert!(!is_invalid2(200));
-    assert!(!is_invalid2(3003));
+    assert!(!at_least_twice.is_invalid(10));
+    assert!(!at_least_twice.is_invalid(200));
+    assert!(!at_least_twice.is_invalid(3003));
+
+    let thrice = RepeatRule::new(3, 3, 10);
+    assert!(thrice.is_invalid(123123123));
+    assert!(!thrice.is_invalid(123123));
+    assert!(!thrice.is_invalid(11111111));
+
+    let hex = RepeatRule::new(2, 2, 16);
+    assert!(hex.is_invalid(0xabab));
+    assert!(!hex.is_invalid(0xababab));
+    assert!(!hex.is_invalid(1010));
+
+    // the generator should find exactly what scanning every number finds
+    let ranges = parse_line(&test_line).into_iter().chain([(1, 99999)]);
+    for (start, end) in ranges {
+        for rule in [twice, at_least_twice, thrice, hex] {
+            let scanned: Vec<u128> = (start..=end).filter(|x| rule.is_invalid(*x)).collect();
+            let generated: Vec<u128> = rule.invalid_ids(start, end).into_iter().collect();
+            assert_eq!(generated, scanned);
+        }
+    }
+
+    // ids longer than fit in i64 or even u64
+    let long_id = 123456789012345_123456789012345;
+    assert!(twice.is_invalid(long_id));
+    assert!(
+        twice
+            .invalid_ids(long_id - 5, long_id + 5)
+            .contains(&long_id)
+    );
+
+    let near_max = at_least_twice.invalid_ids(u128::MAX - 10u128.pow(25), u128::MAX);
+    assert!(near_max.contains(&340282366920934028236692093402823669209));
+    assert!(near_max.iter().all(|&id| at_least_twice.is_invalid(id)));
 
     assert_eq!(part2(&test_line), 4174379265);
     println!("Day 2 part 2 answer: {}", part2(&input_line));
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    /// Reference that works on the decimal string: is it one block repeated an allowed number of times
+    fn is_invalid_string(num: u128, rule: &RepeatRule) -> bool {
+        let digits = num.to_string();
+        let length = digits.len();
+        (1..=length / 2).any(|block_size| {
+            let repeats = length / block_size;
+            length.is_multiple_of(block_size)
+                && (rule.min_repeats..=rule.max_repeats).contains(&(repeats as u32))
+                && digits == digits[..block_size].repeat(repeats)
+        })
+    }
+
+    fn decimal_rule() -> impl Strategy<Value = RepeatRule> {
+        prop_oneof![
+            Just(RepeatRule::TWICE),
+            Just(RepeatRule::AT_LEAST_TWICE),
+            (2..=5u32, 0..=3u32).prop_map(|(min, extra)| RepeatRule::new(min, min + extra, 10)),
+        ]
+    }
+
+    proptest! {
+        #[test]
+        fn is_invalid_matches_string_reference(rule in decimal_rule(), num in any::<u128>()) {
+            prop_assert_eq!(rule.is_invalid(num), is_invalid_string(num, &rule));
+        }
+
+        #[test]
+        fn repeated_blocks_match_string_reference(rule in decimal_rule(), block in 1..100_000u128, repeats in 2..=6u32) {
+            let num = block.to_string().repeat(repeats as usize).parse().unwrap();
+            prop_assert_eq!(rule.is_invalid(num), is_invalid_string(num, &rule));
+        }
+
+        #[test]
+        fn invalid_ids_match_string_reference(
+            rule in decimal_rule(),
+            start in 1..100_000_000u128,
+            width in 0..5_000u128,
+        ) {
+            let end = start + width;
+            let scanned: Vec<u128> =
+                (start..=end).filter(|&x| is_invalid_string(x, &rule)).collect();
+            let generated: Vec<u128> = rule.invalid_ids(start, end).into_iter().collect();
+            prop_assert_eq!(generated, scanned);
+        }
+    }
+}
@@ -1,39 +1,230 @@
-fn solve_line(line: &str, num_digits: usize) -> i64 {
-    let mut output = String::new();
+use std::fmt;
 
-    let digits = line
-        .chars()
-        .map(|c| c.to_digit(10).unwrap() as i64)
-        .collect::<Vec<i64>>();
+use crate::utils::args::Args;
+use crate::utils::lines_from;
 
-    let mut slice_start = 0;
-    for d in 0..num_digits {
-        let slice = &digits[slice_start..digits.len() - (num_digits - d - 1)];
-        let max_digit = slice.iter().max().unwrap();
-        let max_digit_index = slice.iter().position(|&x| x == *max_digit).unwrap();
+/// Problems with the banks
+#[derive(Debug)]
+enum JoltageError {
+    InvalidCharacter {
+        line: usize,
+        character: char,
+    },
+    BankTooShort {
+        line: usize,
+        length: usize,
+        requested: usize,
+    },
+    Overflow,
+}
+
+impl fmt::Display for JoltageError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            JoltageError::InvalidCharacter { line, character } => {
+                write!(f, "line {line}: '{character}' is not a battery joltage")
+            }
+            JoltageError::BankTooShort {
+                line,
+                length,
+                requested,
+            } => write!(
+                f,
+                "line {line}: bank has {length} batteries, {requested} were requested"
+            ),
+            JoltageError::Overflow => write!(f, "total joltage does not fit in u128"),
+        }
+    }
+}
+
+/// Parse every non-blank line into a bank of digits, paired with its line number
+fn parse_banks(lines: &[String]) -> Result<Vec<(usize, Vec<u8>)>, JoltageError> {
+    let mut banks = Vec::new();
+    for (i, line) in lines.iter().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let bank = line
+            .trim()
+            .chars()
+            .map(|c| match c.to_digit(10) {
+                Some(d) => Ok(d as u8),
+                None => Err(JoltageError::InvalidCharacter {
+                    line: i + 1,
+                    character: c,
+                }),
+            })
+            .collect::<Result<Vec<u8>, JoltageError>>()?;
+        banks.push((i + 1, bank));
+    }
+    Ok(banks)
+}
+
+/// The batteries turned on in a bank, in the order they appear
+struct Selection {
+    indices: Vec<usize>,
+    digits: Vec<u8>,
+}
+
+impl Selection {
+    /// Joltage as a number, or None if it has too many digits for u128
+    fn joltage(&self) -> Option<u128> {
+        self.digits
+            .iter()
+            .try_fold(0u128, |acc, &d| acc.checked_mul(10)?.checked_add(d as u128))
+    }
+
+    /// Joltage written out in full, works for any number of digits
+    fn digit_string(&self) -> String {
+        self.digits.iter().map(|d| d.to_string()).collect()
+    }
+}
 
-        output.push_str(&max_digit.to_string());
-        slice_start += max_digit_index + 1;
+/// Pick the largest subsequence of num_digits digits in a single pass
+/// Keeps a stack of chosen digits, and while digits can still be dropped, a larger digit
+/// replaces any smaller ones before it, because an earlier position weighs more
+/// Returns None if the bank has fewer than num_digits batteries
+fn solve_bank(bank: &[u8], num_digits: usize) -> Option<Selection> {
+    let mut drops_left = bank.len().checked_sub(num_digits)?;
+    let mut stack: Vec<(usize, u8)> = Vec::with_capacity(bank.len());
+    for (i, &digit) in bank.iter().enumerate() {
+        while drops_left > 0 && stack.last().is_some_and(|&(_, top)| top < digit) {
+            stack.pop();
+            drops_left -= 1;
+        }
+        stack.push((i, digit));
     }
 
-    output.parse::<i64>().unwrap()
+    // drops that were not needed come off the end, where the digits weigh least
+    stack.truncate(num_digits);
+    Some(Selection {
+        indices: stack.iter().map(|&(i, _)| i).collect(),
+        digits: stack.iter().map(|&(_, d)| d).collect(),
+    })
+}
+
+/// Select num_digits batteries in every bank, checking each bank is long enough
+/// Each selection is paired with the line number of its bank
+fn solve_banks(
+    lines: &[String],
+    num_digits: usize,
+) -> Result<Vec<(usize, Selection)>, JoltageError> {
+    let banks = parse_banks(lines)?;
+    banks
+        .iter()
+        .map(|(line, bank)| {
+            let selection = solve_bank(bank, num_digits).ok_or(JoltageError::BankTooShort {
+                line: *line,
+                length: bank.len(),
+                requested: num_digits,
+            })?;
+            Ok((*line, selection))
+        })
+        .collect()
 }
 
-fn part1(lines: &[String]) -> i64 {
-    lines.iter().map(|line| solve_line(line, 2)).sum()
+fn sum_joltage(selections: &[(usize, Selection)]) -> Result<u128, JoltageError> {
+    selections
+        .iter()
+        .try_fold(0u128, |acc, (_, s)| acc.checked_add(s.joltage()?))
+        .ok_or(JoltageError::Overflow)
 }
 
-fn part2(lines: &[String]) -> i64 {
-    lines.iter().map(|line| solve_line(line, 12)).sum()
+fn total_joltage(lines: &[String], num_digits: usize) -> Result<u128, JoltageError> {
+    sum_joltage(&solve_banks(lines, num_digits)?)
+}
+
+fn part1(lines: &[String]) -> Result<u128, JoltageError> {
+    total_joltage(lines, 2)
+}
+
+fn part2(lines: &[String]) -> Result<u128, JoltageError> {
+    total_joltage(lines, 12)
+}
+
+/// Total joltage for any number of batteries per bank, usage:
+/// `day03 solve --digits=12 [--input=input/day03.txt] [--banks]`
+/// With `--banks` the selected joltage of every bank is printed in full, by input line number
+fn run_solve(args: &Args) {
+    let input_path = args.value("input").unwrap_or("input/day03.txt");
+    let num_digits: usize = args.parsed_or("digits", 12);
+
+    let lines = crate::utils::read_lines(input_path);
+    let selections = solve_banks(&lines, num_digits).unwrap_or_else(|e| panic!("{e}"));
+    if args.flag("banks") {
+        for (line, selection) in &selections {
+            println!("Line {line}: {}", selection.digit_string());
+        }
+    }
+
+    match sum_joltage(&selections) {
+        Ok(total) => println!("Total joltage with {num_digits} batteries per bank: {total}"),
+        Err(e) => println!("Total joltage with {num_digits} batteries per bank: {e}"),
+    }
+}
+
+pub fn cli(args: &[String]) {
+    let args = Args::parse(args);
+    match args.positional(0) {
+        None => main(),
+        Some("solve") => run_solve(&args),
+        Some(command) => panic!("Unknown day 3 command: {command}"),
+    }
 }
 
 pub fn main() {
     let test_lines = crate::utils::read_lines("input_test/day03.txt");
     let input_lines = crate::utils::read_lines("input/day03.txt");
 
-    assert_eq!(part1(&test_lines), 357);
-    println!("Day 3 part 1 answer: {}", part1(&input_lines));
+    let banks = parse_banks(&test_lines).unwrap();
+    let selection = solve_bank(&banks[3].1, 12).unwrap();
+    assert_eq!(selection.joltage(), Some(888911112111));
+    assert_eq!(
+        selection.indices,
+        vec![0, 2, 4, 6, 7, 8, 9, 10, 11, 12, 13, 14]
+    );
+    let selection = solve_bank(&banks[2].1, 15).unwrap();
+    assert!(solve_bank(&banks[2].1, 16).is_none());
+    assert_eq!(selection.indices, (0..15).collect::<Vec<_>>());
+    assert_eq!(selection.digit_string(), "234234234234278");
+
+    let long_bank = [9; 50];
+    let selection = solve_bank(&long_bank, 40).unwrap();
+    assert_eq!(selection.joltage(), None);
+    assert_eq!(selection.digit_string(), "9".repeat(40));
+    assert!(solve_bank(&long_bank, 38).unwrap().joltage().is_some());
+
+    assert!(matches!(
+        total_joltage(&lines_from(&["12345", "12a45"]), 2),
+        Err(JoltageError::InvalidCharacter {
+            line: 2,
+            character: 'a'
+        })
+    ));
+    assert_eq!(
+        total_joltage(&lines_from(&["", "12345", " ", ""]), 2).unwrap(),
+        45
+    );
+    let selections = solve_banks(&lines_from(&["", "12345", " ", "987"]), 2).unwrap();
+    assert_eq!(
+        selections
+            .iter()
+            .map(|(line, s)| (*line, s.digit_string()))
+            .collect::<Vec<_>>(),
+        vec![(2, "45".to_string()), (4, "98".to_string())]
+    );
+    assert!(matches!(
+        total_joltage(&lines_from(&["12345", "", "123"]), 4),
+        Err(JoltageError::BankTooShort {
+            line: 3,
+            length: 3,
+            requested: 4
+        })
+    ));
+
+    assert_eq!(part1(&test_lines).unwrap(), 357);
+    println!("Day 3 part 1 answer: {}", part1(&input_lines).unwrap());
 
-    assert_eq!(part2(&test_lines), 3121910778619);
-    println!("Day 3 part 2 answer: {}", part2(&input_lines));
+    assert_eq!(part2(&test_lines).unwrap(), 3121910778619);
+    println!("Day 3 part 2 answer: {}", part2(&input_lines).unwrap());
 }
@@ -1,65 +1,372 @@
-use crate::utils::direction::{Direction, move_in_direction};
+use crate::utils::args::Args;
+use crate::utils::automaton::{Automaton, Update};
+use crate::utils::direction::Direction;
 use crate::utils::grid::Grid2D;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs;
+use std::path::Path;
+use std::thread;
+use std::time::Duration;
 
 type PaperGrid = HashMap<(i32, i32), char>;
 
-/// Get all accessible paper positions ('@') that have less than 4 adjacent papers
-fn get_accessible(grid: &PaperGrid) -> Vec<(i32, i32)> {
-    let mut accessible = Vec::new();
+/// Which rolls a forklift can reach: rolls with less than `threshold` rolls among their neighbours
+#[derive(Clone, Debug)]
+struct Accessibility {
+    roll: char,
+    neighbourhood: Vec<(i32, i32)>,
+    threshold: usize,
+}
 
-    for (pos, val) in grid {
-        if *val != '@' {
-            continue;
+impl Accessibility {
+    /// The rule from the puzzle: '@' rolls with less than 4 rolls in the 8 surrounding cells
+    fn puzzle() -> Self {
+        Accessibility {
+            roll: '@',
+            neighbourhood: parse_neighbourhood("8").unwrap(),
+            threshold: 4,
         }
+    }
 
-        let adj_paper_count = Direction::all_directions()
+    fn neighbours(&self, (x, y): (i32, i32)) -> impl Iterator<Item = (i32, i32)> {
+        self.neighbourhood
             .iter()
-            .map(|d| move_in_direction(*pos, d, 1))
-            .map(|p| grid.get_or_default(&p, '.'))
-            .filter(|&c| c == '@')
-            .count();
+            .map(move |(dx, dy)| (x + dx, y + dy))
+    }
 
-        if adj_paper_count < 4 {
-            accessible.push(*pos);
-        }
+    /// The cells that have this position as a neighbour, which differ from its neighbours
+    /// when a custom neighbourhood is not symmetric
+    fn dependents(&self, (x, y): (i32, i32)) -> impl Iterator<Item = (i32, i32)> {
+        self.neighbourhood
+            .iter()
+            .map(move |(dx, dy)| (x - dx, y - dy))
     }
+}
 
-    accessible
+/// Parse a neighbourhood: `4` for the straight neighbours, `8` to include the diagonals,
+/// or custom offsets like `0,-2;0,2`
+fn parse_neighbourhood(text: &str) -> Option<Vec<(i32, i32)>> {
+    let directions = match text {
+        "4" => Direction::straight_directions(),
+        "8" => Direction::all_directions(),
+        _ => {
+            return text
+                .split(';')
+                .map(|offset| {
+                    let (dx, dy) = offset.split_once(',')?;
+                    Some((dx.trim().parse().ok()?, dy.trim().parse().ok()?))
+                })
+                .collect();
+        }
+    };
+    Some(directions.iter().map(|d| d.value()).collect())
+}
+
+/// Accessible rolls are removed every generation
+fn removal(rule: &Accessibility, update: Update) -> Automaton<char> {
+    let Accessibility {
+        roll, threshold, ..
+    } = *rule;
+    Automaton::new(
+        rule.neighbourhood.clone(),
+        update,
+        move |cell, neighbours| {
+            let adj_paper_count = neighbours.iter().filter(|&&c| c == roll).count();
+            if cell == roll && adj_paper_count < threshold {
+                '.'
+            } else {
+                cell
+            }
+        },
+    )
+}
+
+fn count_paper(grid: &PaperGrid, roll: char) -> usize {
+    grid.values().filter(|&&c| c == roll).count()
+}
+
+fn count_accessible(grid: &PaperGrid, rule: &Accessibility) -> usize {
+    // every roll removed in the first generation was accessible
+    removal(rule, Update::Synchronous).step(&mut grid.clone())
 }
 
 fn part1(lines: &[String]) -> i32 {
     let grid = HashMap::from_lines(lines);
-    get_accessible(&grid).len() as i32
+    count_accessible(&grid, &Accessibility::puzzle()) as i32
 }
 
-fn part2(lines: &[String]) -> i32 {
+/// Remove accessible rolls until none are left, returning the removed count and the number of rounds
+fn remove_all(lines: &[String], rule: &Accessibility, update: Update) -> (usize, usize) {
     let mut grid = HashMap::from_lines(lines);
-    let mut answer = 0;
-    let mut done = false;
+    let before = count_paper(&grid, rule.roll);
+    let generations = removal(rule, update).run_to_fixpoint(&mut grid);
+    (before - count_paper(&grid, rule.roll), generations)
+}
+
+/// Rolls removed in each round, where a round removes every roll that is accessible at its start
+#[derive(Debug, PartialEq)]
+struct Removal {
+    rounds: Vec<Vec<(i32, i32)>>,
+}
+
+impl Removal {
+    fn per_round(&self) -> Vec<usize> {
+        self.rounds.iter().map(|round| round.len()).collect()
+    }
+
+    fn total(&self) -> usize {
+        self.rounds.iter().map(|round| round.len()).sum()
+    }
+}
+
+/// Remove accessible rolls with a worklist instead of rescanning the grid every round
+/// The number of adjacent rolls is kept for every roll, and a removal only updates the rolls next to it
+/// A roll is queued once, when its count drops below the threshold, so this takes O(cells) time
+/// The queue is first in first out, so the rolls of one round are all removed before the next round
+fn remove_with_worklist(grid: &PaperGrid, rule: &Accessibility) -> Removal {
+    let mut counts: HashMap<(i32, i32), usize> = grid
+        .iter()
+        .filter(|&(_, &c)| c == rule.roll)
+        .map(|(&pos, _)| {
+            let count = rule
+                .neighbours(pos)
+                .filter(|p| grid.get(p) == Some(&rule.roll))
+                .count();
+            (pos, count)
+        })
+        .collect();
+    let mut queue: VecDeque<((i32, i32), usize)> = counts
+        .iter()
+        .filter(|&(_, &count)| count < rule.threshold)
+        .map(|(&pos, _)| (pos, 0))
+        .collect();
+
+    let mut rounds = Vec::new();
+    while let Some((pos, round)) = queue.pop_front() {
+        counts.remove(&pos);
+        if round == rounds.len() {
+            rounds.push(Vec::new());
+        }
+        rounds[round].push(pos);
+
+        for dependent in rule.dependents(pos) {
+            if let Some(count) = counts.get_mut(&dependent) {
+                // only queue the roll when it crosses the threshold, it is already queued if it was below
+                if *count == rule.threshold {
+                    queue.push_back((dependent, round + 1));
+                }
+                *count -= 1;
+            }
+        }
+    }
+    Removal { rounds }
+}
+
+fn part2(lines: &[String]) -> Removal {
+    let grid = HashMap::from_lines(lines);
+    remove_with_worklist(&grid, &Accessibility::puzzle())
+}
+
+/// The rule given by the `--roll`, `--neighbourhood` and `--threshold` options, defaulting to the puzzle
+fn rule_from_args(args: &Args) -> Accessibility {
+    let neighbourhood = args.value("neighbourhood").unwrap_or("8");
+    Accessibility {
+        roll: args.parsed_or("roll", '@'),
+        neighbourhood: parse_neighbourhood(neighbourhood)
+            .unwrap_or_else(|| panic!("Invalid value for --neighbourhood: {neighbourhood}")),
+        threshold: args.parsed_or("threshold", 4),
+    }
+}
 
-    while !done {
-        let accessible = get_accessible(&grid);
-        done = accessible.is_empty();
+/// Draw the grid with the given rolls marked 'x', as in the puzzle description
+fn render_frame(grid: &PaperGrid, removed: &HashSet<(i32, i32)>) -> String {
+    let width = grid.keys().map(|&(x, _)| x + 1).max().unwrap_or(0);
+    let height = grid.keys().map(|&(_, y)| y + 1).max().unwrap_or(0);
 
-        answer += accessible.len() as i32;
+    let mut frame = String::new();
+    for y in 0..height {
+        for x in 0..width {
+            if removed.contains(&(x, y)) {
+                frame.push('x');
+            } else {
+                frame.push(grid.get_or_default(&(x, y), ' '));
+            }
+        }
+        frame.push('\n');
+    }
+    frame
+}
 
-        for pos in accessible {
-            // mark position as processed
-            grid.insert(pos, '.');
+/// Every round of the removal as a frame, followed by the grid that is left at the end
+fn removal_frames(grid: &PaperGrid, rule: &Accessibility) -> Vec<String> {
+    let removal = remove_with_worklist(grid, rule);
+    let mut grid = grid.clone();
+    let mut frames = Vec::new();
+    for (round, positions) in removal.rounds.iter().enumerate() {
+        let removed: HashSet<(i32, i32)> = positions.iter().copied().collect();
+        frames.push(format!(
+            "Round {}: remove {} rolls of paper\n{}",
+            round + 1,
+            removed.len(),
+            render_frame(&grid, &removed)
+        ));
+        for position in removed {
+            grid.insert(position, '.');
+        }
+    }
+    frames.push(format!(
+        "Done: removed {} rolls of paper\n{}",
+        removal.total(),
+        render_frame(&grid, &HashSet::new())
+    ));
+    frames
+}
+
+/// Animate the removal rounds in the terminal, usage:
+/// `day04 animate [--input=input/day04.txt] [--delay=500] [--frames=dir]` and the rule options of `remove`
+/// The delay between frames is in milliseconds, with `--frames` every frame is also written to
+/// a numbered text file in that directory
+fn run_animate(args: &Args) {
+    let input_path = args.value("input").unwrap_or("input/day04.txt");
+    let delay = Duration::from_millis(args.parsed_or("delay", 500));
+    let frames_dir = args.value("frames");
+
+    let grid = HashMap::from_lines(&crate::utils::read_lines(input_path));
+    let frames = removal_frames(&grid, &rule_from_args(args));
+
+    if let Some(dir) = frames_dir {
+        fs::create_dir_all(dir).expect("should be able to create frames directory");
+    }
+    for (i, frame) in frames.iter().enumerate() {
+        // clear the screen and move the cursor back to the top left
+        print!("\x1b[2J\x1b[H{frame}");
+        if let Some(dir) = frames_dir {
+            let path = Path::new(dir).join(format!("frame_{i:04}.txt"));
+            fs::write(path, frame).expect("should be able to write file");
+        }
+        if i + 1 < frames.len() {
+            thread::sleep(delay);
         }
     }
+    if let Some(dir) = frames_dir {
+        println!("{} frames written to {dir}", frames.len());
+    }
+}
+
+/// Count accessible rolls and remove them round by round under a custom rule, usage:
+/// `day04 remove [--input=input/day04.txt] [--roll=@] [--neighbourhood=8] [--threshold=4]`
+/// The neighbourhood is `4`, `8` or a list of offsets like `--neighbourhood=-1,0;1,0;0,-2;0,2`
+fn run_remove(args: &Args) {
+    let input_path = args.value("input").unwrap_or("input/day04.txt");
+    let rule = rule_from_args(args);
+
+    let grid = HashMap::from_lines(&crate::utils::read_lines(input_path));
+    println!("Accessible rolls: {}", count_accessible(&grid, &rule));
 
-    answer
+    let removal = remove_with_worklist(&grid, &rule);
+    for (round, removed) in removal.per_round().iter().enumerate() {
+        println!("Round {}: removed {removed} rolls", round + 1);
+    }
+    println!(
+        "Removed {} rolls in {} rounds",
+        removal.total(),
+        removal.rounds.len()
+    );
+}
+
+pub fn cli(args: &[String]) {
+    let args = Args::parse(args);
+    match args.positional(0) {
+        None => main(),
+        Some("remove") => run_remove(&args),
+        Some("animate") => run_animate(&args),
+        Some(command) => panic!("Unknown day 4 command: {command}"),
+    }
 }
 
 pub fn main() {
     let test_lines = crate::utils::read_lines("input_test/day04.txt");
     let input_lines = crate::utils::read_lines("input/day04.txt");
 
+    // fire spreading right along a row takes a generation per cell when updated synchronously,
+    // but a single generation when every cell already sees the one before it burning
+    let spread = |update| {
+        Automaton::new(vec![(-1, 0)], update, |cell, neighbours: &[char]| {
+            if neighbours.contains(&'#') { '#' } else { cell }
+        })
+    };
+    let row = |s: &str| HashMap::from_lines(&[s.to_string()]);
+    let mut grid = row("#....");
+    assert_eq!(spread(Update::Synchronous).run_to_fixpoint(&mut grid), 4);
+    assert_eq!(grid, row("#####"));
+    let mut grid = row("#....");
+    assert_eq!(spread(Update::Asynchronous).run_to_fixpoint(&mut grid), 1);
+    assert_eq!(grid, row("#####"));
+
     assert_eq!(part1(&test_lines), 13);
     println!("Day 4 part 1 answer: {}", part1(&input_lines));
 
-    assert_eq!(part2(&test_lines), 43);
-    println!("Day 4 part 2 answer: {}", part2(&input_lines));
+    // removing rolls only frees up others, so the update order does not change the outcome,
+    // and the rounds of the worklist are the generations of the synchronous automaton
+    let puzzle = Accessibility::puzzle();
+    let removal = part2(&test_lines);
+    assert_eq!(removal.per_round(), vec![13, 12, 7, 5, 2, 1, 1, 1, 1]);
+    for update in [Update::Synchronous, Update::Asynchronous] {
+        assert_eq!(remove_all(&test_lines, &puzzle, update).0, 43);
+    }
+    assert_eq!(
+        remove_all(&test_lines, &puzzle, Update::Synchronous).1,
+        removal.rounds.len()
+    );
+
+    // other rules, checked against the automaton
+    let rules = [
+        (parse_neighbourhood("4").unwrap(), 2),
+        (parse_neighbourhood("8").unwrap(), 6),
+        (parse_neighbourhood("-1,0;1,0;0,-2;0,2").unwrap(), 3),
+        (parse_neighbourhood("1,0;1,1;0,1").unwrap(), 2),
+    ];
+    let grid = HashMap::from_lines(&test_lines);
+    for (neighbourhood, threshold) in rules {
+        let rule = Accessibility {
+            neighbourhood,
+            threshold,
+            ..Accessibility::puzzle()
+        };
+        let removal = remove_with_worklist(&grid, &rule);
+        let (removed, generations) = remove_all(&test_lines, &rule, Update::Synchronous);
+        assert_eq!(
+            (removal.total(), removal.rounds.len()),
+            (removed, generations)
+        );
+    }
+    assert_eq!(parse_neighbourhood("1,x"), None);
+
+    let hashes = test_lines
+        .iter()
+        .map(|l| l.replace('@', "#"))
+        .collect::<Vec<_>>();
+    let hash_rolls = Accessibility {
+        roll: '#',
+        ..Accessibility::puzzle()
+    };
+    assert_eq!(
+        count_accessible(&HashMap::from_lines(&hashes), &hash_rolls),
+        13
+    );
+
+    let frames = removal_frames(&grid, &puzzle);
+    assert_eq!(frames.len(), removal.rounds.len() + 1);
+    assert!(frames[0].starts_with("Round 1: remove 13 rolls of paper\n..xx.xx@x.\nx@@.@.@.@@\n"));
+    assert!(frames[9].starts_with("Done: removed 43 rolls of paper\n..........\n"));
+    assert!(frames[9].ends_with("...@@@@@..\n....@@@...\n"));
+
+    assert_eq!(removal.total(), 43);
+    let removal = part2(&input_lines);
+    println!(
+        "Day 4 part 2 answer: {} (in {} rounds: {:?})",
+        removal.total(),
+        removal.rounds.len(),
+        removal.per_round()
+    );
 }
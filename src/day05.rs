@@ -1,68 +1,233 @@
-fn prep_data(lines: &[String]) -> (Vec<(i64, i64)>, Vec<i64>) {
+use std::fmt;
+
+use crate::utils::args::Args;
+use crate::utils::intervals::RangeSet;
+use crate::utils::lines_from;
+
+type Range = (i64, i64);
+
+/// Lines that could not be parsed
+#[derive(Debug)]
+enum InputError {
+    MalformedRange { line: usize, text: String },
+    MalformedId { line: usize, text: String },
+}
+
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InputError::MalformedRange { line, text } => {
+                write!(f, "line {line}: cannot parse range from '{text}'")
+            }
+            InputError::MalformedId { line, text } => {
+                write!(f, "line {line}: cannot parse ingredient id from '{text}'")
+            }
+        }
+    }
+}
+
+/// Parse a range like `3-5`, a reversed range like `5-3` is read as the same range
+fn parse_range(line: &str) -> Option<Range> {
+    let (start, end) = line.trim().split_once('-')?;
+    let (start, end): (i64, i64) = (start.parse().ok()?, end.parse().ok()?);
+    Some((start.min(end), start.max(end)))
+}
+
+fn prep_data(lines: &[String]) -> Result<(Vec<Range>, Vec<i64>), InputError> {
     let mut is_range_part = true;
-    let mut ranges: Vec<(i64, i64)> = Vec::new();
+    let mut ranges: Vec<Range> = Vec::new();
     let mut ids: Vec<i64> = Vec::new();
-    for line in lines {
-        if line.is_empty() {
+    for (i, line) in lines.iter().enumerate() {
+        if line.trim().is_empty() {
             is_range_part = false;
             continue;
         }
 
         if is_range_part {
-            let parts: Vec<&str> = line.split('-').collect();
-            let start: i64 = parts[0].parse().unwrap();
-            let end: i64 = parts[1].parse().unwrap();
-            ranges.push((start, end));
+            let range = parse_range(line).ok_or_else(|| InputError::MalformedRange {
+                line: i + 1,
+                text: line.clone(),
+            })?;
+            ranges.push(range);
         } else {
-            ids.push(line.parse().unwrap());
+            let id = line.trim().parse().map_err(|_| InputError::MalformedId {
+                line: i + 1,
+                text: line.clone(),
+            })?;
+            ids.push(id);
         }
     }
-    (ranges, ids)
+    Ok((ranges, ids))
 }
 
-fn part1(lines: &[String]) -> i32 {
-    let (ranges, ids) = prep_data(lines);
+fn part1(lines: &[String]) -> Result<usize, InputError> {
+    let (ranges, ids) = prep_data(lines)?;
+    let fresh: RangeSet = ranges.into_iter().collect();
 
-    // check each id against the ranges
-    ids.iter()
-        .filter(|&&id| ranges.iter().any(|&(start, end)| id >= start && id <= end))
-        .count() as i32
+    // check each id against the merged ranges
+    Ok(ids.iter().filter(|&&id| fresh.contains(id)).count())
 }
 
-fn part2(lines: &[String]) -> i64 {
-    let mut answer = 0;
-    let (mut ranges, _) = prep_data(lines);
+fn part2(lines: &[String]) -> Result<u128, InputError> {
+    let (ranges, _) = prep_data(lines)?;
+    let fresh: RangeSet = ranges.into_iter().collect();
+
+    // overlapping ranges are merged on insert, so nothing is counted twice
+    Ok(fresh.covered_length())
+}
 
-    // sort them on start ascending
-    ranges.sort_by(|a, b| a.0.cmp(&b.0));
+/// What the ranges say about a single ingredient id, as indices into the original ranges
+#[derive(Debug, PartialEq)]
+enum Coverage {
+    /// Every range that contains the id, in input order
+    Fresh(Vec<usize>),
+    /// The id falls in a gap, with the closest range on either side if there is one
+    Spoiled {
+        below: Option<usize>,
+        above: Option<usize>,
+    },
+}
 
-    // merge overlapping ranges by comparing current end to next start
-    let (mut start, mut end) = ranges[0];
-    for (next_start, next_end) in ranges.iter().skip(1) {
-        if *next_start <= end {
-            // ranges overlap, extend the end if needed
-            end = end.max(*next_end);
-        } else {
-            // no overlap, add the current range to the answer and start a new one
-            answer += end - start + 1;
-            start = *next_start;
-            end = *next_end;
-        }
+fn query(ranges: &[Range], id: i64) -> Coverage {
+    let containing: Vec<usize> = (0..ranges.len())
+        .filter(|&i| ranges[i].0 <= id && id <= ranges[i].1)
+        .collect();
+    if !containing.is_empty() {
+        return Coverage::Fresh(containing);
     }
 
-    // add the last range
-    answer += end - start + 1;
+    // closest below is the range ending last before the id, closest above the one starting first after it
+    let below = (0..ranges.len())
+        .filter(|&i| ranges[i].1 < id)
+        .min_by_key(|&i| id.abs_diff(ranges[i].1));
+    let above = (0..ranges.len())
+        .filter(|&i| ranges[i].0 > id)
+        .min_by_key(|&i| ranges[i].0.abs_diff(id));
+    Coverage::Spoiled { below, above }
+}
+
+/// Describe a range along with the input line it came from
+fn describe_range(ranges: &[Range], index: usize) -> String {
+    let (start, end) = ranges[index];
+    format!("{start}-{end} (line {})", index + 1)
+}
+
+/// Report for every ingredient id which ranges cover it, or which ranges surround it, usage:
+/// `day05 query [--input=input/day05.txt]`
+fn run_query(args: &Args) {
+    let input_path = args.value("input").unwrap_or("input/day05.txt");
+    let lines = crate::utils::read_lines(input_path);
+    let (ranges, ids) = prep_data(&lines).unwrap_or_else(|e| panic!("Invalid day 5 input: {e}"));
 
-    answer
+    for id in ids {
+        match query(&ranges, id) {
+            Coverage::Fresh(containing) => {
+                let described = containing.iter().map(|&i| describe_range(&ranges, i));
+                println!(
+                    "{id}: fresh, in {}",
+                    described.collect::<Vec<_>>().join(", ")
+                );
+            }
+            Coverage::Spoiled { below, above } => {
+                let describe = |index: Option<usize>| {
+                    index.map_or("none".to_string(), |i| describe_range(&ranges, i))
+                };
+                println!(
+                    "{id}: spoiled, nearest below {}, nearest above {}",
+                    describe(below),
+                    describe(above)
+                );
+            }
+        }
+    }
+}
+
+pub fn cli(args: &[String]) {
+    let args = Args::parse(args);
+    match args.positional(0) {
+        None => main(),
+        Some("query") => run_query(&args),
+        Some(command) => panic!("Unknown day 5 command: {command}"),
+    }
 }
 
 pub fn main() {
     let test_lines = crate::utils::read_lines("input_test/day05.txt");
     let input_lines = crate::utils::read_lines("input/day05.txt");
 
-    assert_eq!(part1(&test_lines), 3);
-    println!("Day 5 part 1 answer: {}", part1(&input_lines));
+    let a: RangeSet = [(1, 5), (10, 20), (8, 9)].into_iter().collect();
+    let b: RangeSet = [(3, 12), (15, 16), (30, 40)].into_iter().collect();
+    assert_eq!(a.iter().collect::<Vec<_>>(), vec![(1, 5), (8, 20)]);
+    assert!(a.contains(9) && !a.contains(6) && !a.contains(21));
+    assert_eq!(a.covered_length(), 18);
+    let mut reversed = RangeSet::new();
+    reversed.insert(10, 3);
+    reversed.insert(4, 6);
+    reversed.insert(14, 12);
+    assert_eq!(reversed.iter().collect::<Vec<_>>(), vec![(3, 10), (12, 14)]);
+    assert_eq!(reversed.covered_length(), 11);
+    assert_eq!(
+        a.union(&b).iter().collect::<Vec<_>>(),
+        vec![(1, 20), (30, 40)]
+    );
+    assert_eq!(
+        a.intersection(&b).iter().collect::<Vec<_>>(),
+        vec![(3, 5), (8, 12), (15, 16)]
+    );
+    assert_eq!(
+        a.difference(&b).iter().collect::<Vec<_>>(),
+        vec![(1, 2), (13, 14), (17, 20)]
+    );
+
+    assert_eq!(part1(&lines_from(&[])).unwrap(), 0);
+    assert_eq!(part2(&lines_from(&[])).unwrap(), 0);
+    assert_eq!(part1(&lines_from(&["10-3", "", "5", "11"])).unwrap(), 1);
+    assert_eq!(part2(&lines_from(&["10-3", "4-4"])).unwrap(), 8);
+    assert_eq!(
+        part2(&lines_from(&[
+            "0-9223372036854775807",
+            "5-9223372036854775807"
+        ]))
+        .unwrap(),
+        1 << 63
+    );
+    assert!(matches!(
+        part2(&lines_from(&["3-5", "3-x"])),
+        Err(InputError::MalformedRange { line: 2, .. })
+    ));
+    assert!(matches!(
+        part1(&lines_from(&["3-5", "", "4", "four"])),
+        Err(InputError::MalformedId { line: 4, .. })
+    ));
+
+    let (test_ranges, _) = prep_data(&test_lines).unwrap();
+    assert_eq!(query(&test_ranges, 17), Coverage::Fresh(vec![2, 3]));
+    assert_eq!(query(&test_ranges, 5), Coverage::Fresh(vec![0]));
+    assert_eq!(
+        query(&test_ranges, 8),
+        Coverage::Spoiled {
+            below: Some(0),
+            above: Some(1)
+        }
+    );
+    assert_eq!(
+        query(&test_ranges, 1),
+        Coverage::Spoiled {
+            below: None,
+            above: Some(0)
+        }
+    );
+    assert_eq!(
+        query(&test_ranges, 32),
+        Coverage::Spoiled {
+            below: Some(2),
+            above: None
+        }
+    );
+
+    assert_eq!(part1(&test_lines).unwrap(), 3);
+    println!("Day 5 part 1 answer: {}", part1(&input_lines).unwrap());
 
-    assert_eq!(part2(&test_lines), 14);
-    println!("Day 5 part 2 answer: {}", part2(&input_lines));
+    assert_eq!(part2(&test_lines).unwrap(), 14);
+    println!("Day 5 part 2 answer: {}", part2(&input_lines).unwrap());
 }
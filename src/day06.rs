@@ -1,74 +1,295 @@
-fn calculate(numbers: &[i32], operator: &str) -> i64 {
-    match operator {
-        "+" => numbers.iter().map(|&n| n as i64).sum(),
-        "*" => numbers.iter().map(|&n| n as i64).product(),
-        _ => 0,
+use std::fmt;
+
+use crate::utils::lines_from;
+
+/// Problems with the worksheet
+#[derive(Debug)]
+enum InputError {
+    MissingOperators,
+    UnknownOperator {
+        problem: usize,
+        text: String,
+    },
+    MalformedNumber {
+        line: usize,
+        text: String,
+    },
+    MalformedColumn {
+        column: usize,
+        text: String,
+    },
+    OperandCountMismatch {
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+    ProblemCountMismatch {
+        expected: usize,
+        found: usize,
+    },
+}
+
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InputError::MissingOperators => write!(f, "worksheet has no line of operators"),
+            InputError::UnknownOperator { problem, text } => {
+                write!(f, "problem {problem}: unknown operator '{text}'")
+            }
+            InputError::MalformedNumber { line, text } => {
+                write!(f, "line {line}: cannot parse number from '{text}'")
+            }
+            InputError::MalformedColumn { column, text } => {
+                write!(f, "column {column}: cannot parse number from '{text}'")
+            }
+            InputError::OperandCountMismatch {
+                line,
+                expected,
+                found,
+            } => write!(
+                f,
+                "line {line}: found {found} numbers, expected one for each of the {expected} operators"
+            ),
+            InputError::ProblemCountMismatch { expected, found } => write!(
+                f,
+                "found {found} problems, expected one for each of the {expected} operators"
+            ),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Operator {
+    Add,
+    Multiply,
+}
+
+impl Operator {
+    fn parse(problem: usize, text: &str) -> Result<Self, InputError> {
+        match text {
+            "+" => Ok(Operator::Add),
+            "*" => Ok(Operator::Multiply),
+            _ => Err(InputError::UnknownOperator {
+                problem,
+                text: text.to_string(),
+            }),
+        }
     }
 }
 
-fn part1(lines: &[String]) -> i64 {
-    let mut answer = 0;
-
-    // data prep
-    let operators: Vec<&str> = lines.last().unwrap().split_whitespace().collect();
-    let mut digits: Vec<Vec<i32>> = Vec::new();
-    for line in lines.iter().take(lines.len() - 1) {
-        let row: Vec<i32> = line
-            .split_whitespace()
-            .map(|num| num.parse::<i32>().unwrap())
-            .collect();
-        digits.push(row);
+/// A single problem of the worksheet: all operands combined with the same operator
+#[derive(Debug, PartialEq)]
+struct Problem {
+    operands: Vec<u64>,
+    operator: Operator,
+}
+
+impl Problem {
+    /// Panics instead of wrapping if the answer does not fit in i128
+    fn solve(&self) -> i128 {
+        let mut operands = self.operands.iter().map(|&n| n as i128);
+        match self.operator {
+            Operator::Add => operands.try_fold(0i128, |acc, n| acc.checked_add(n)),
+            Operator::Multiply => operands.try_fold(1i128, |acc, n| acc.checked_mul(n)),
+        }
+        .expect("answer should fit in i128")
     }
+}
 
-    // loop through the columns
-    for col in 0..operators.len() {
-        let col_values: Vec<i32> = digits.iter().map(|r| r[col]).collect();
-        answer += calculate(&col_values, operators[col]);
+/// The lines as rows of characters, all padded with spaces to the width of the longest line,
+/// so columns can be indexed directly even when trailing spaces were stripped from the input
+fn padded_grid(lines: &[String]) -> Vec<Vec<char>> {
+    let mut grid: Vec<Vec<char>> = lines.iter().map(|l| l.chars().collect()).collect();
+    let width = grid.iter().map(|row| row.len()).max().unwrap_or(0);
+    for row in &mut grid {
+        row.resize(width, ' ');
     }
+    grid
+}
 
-    answer
+#[derive(Debug, PartialEq)]
+struct Worksheet {
+    problems: Vec<Problem>,
 }
 
-fn part2(lines: &[String]) -> i64 {
-    let mut answer = 0;
+impl Worksheet {
+    /// Split off the operator line at the bottom of the worksheet
+    fn split_operators(lines: &[String]) -> Result<(&[String], Vec<&str>), InputError> {
+        let (operator_line, number_lines) =
+            lines.split_last().ok_or(InputError::MissingOperators)?;
+        Ok((number_lines, operator_line.split_whitespace().collect()))
+    }
 
-    // data prep
-    let mut operators = lines.last().unwrap().split_whitespace(); // iterator
-    let number_lines = lines[..lines.len() - 1].to_vec();
+    /// Read the worksheet as people do: each problem is a column of numbers written left to right
+    fn read_rows(lines: &[String]) -> Result<Self, InputError> {
+        let (number_lines, operators) = Worksheet::split_operators(lines)?;
 
-    let mut numbers: Vec<i32> = Vec::new();
-    for c in 0..lines[0].chars().count() {
-        let digits = number_lines
+        let mut problems: Vec<Problem> = operators
             .iter()
-            .map(|l| l.chars().nth(c).unwrap())
-            .filter(|d| *d != ' ')
-            .collect::<Vec<char>>();
-
-        if digits.is_empty() {
-            // calculate the answer for this column, add it to the total answer, and reset numbers
-            let op = operators.next().unwrap();
-            answer += calculate(&numbers, op);
-            numbers = Vec::new();
-            continue;
+            .enumerate()
+            .map(|(i, text)| {
+                Ok(Problem {
+                    operands: Vec::new(),
+                    operator: Operator::parse(i + 1, text)?,
+                })
+            })
+            .collect::<Result<_, InputError>>()?;
+
+        for (i, line) in number_lines.iter().enumerate() {
+            let numbers = line
+                .split_whitespace()
+                .map(|text| {
+                    text.parse::<u64>()
+                        .map_err(|_| InputError::MalformedNumber {
+                            line: i + 1,
+                            text: text.to_string(),
+                        })
+                })
+                .collect::<Result<Vec<u64>, InputError>>()?;
+            if numbers.len() != problems.len() {
+                return Err(InputError::OperandCountMismatch {
+                    line: i + 1,
+                    expected: problems.len(),
+                    found: numbers.len(),
+                });
+            }
+
+            for (problem, number) in problems.iter_mut().zip(numbers) {
+                problem.operands.push(number);
+            }
         }
 
-        numbers.push(digits.iter().collect::<String>().parse::<i32>().unwrap());
+        Ok(Worksheet { problems })
     }
 
-    // final calculation for the last column
-    let op = operators.next().unwrap();
-    answer += calculate(&numbers, op);
+    /// Read the worksheet as cephalopods do: right to left, one number per column with its
+    /// digits from top to bottom, and problems separated by a column of spaces
+    fn read_columns(lines: &[String]) -> Result<Self, InputError> {
+        let (number_lines, operators) = Worksheet::split_operators(lines)?;
+        let grid = padded_grid(number_lines);
+        let width = grid.first().map_or(0, |row| row.len());
+
+        let mut groups: Vec<Vec<u64>> = Vec::new();
+        let mut operands: Vec<u64> = Vec::new();
+        for c in (0..width).rev() {
+            let digits = grid
+                .iter()
+                .map(|row| row[c])
+                .filter(|d| *d != ' ')
+                .collect::<String>();
 
-    answer
+            if digits.is_empty() {
+                groups.push(std::mem::take(&mut operands));
+                continue;
+            }
+
+            let number = digits.parse().map_err(|_| InputError::MalformedColumn {
+                column: c + 1,
+                text: digits.clone(),
+            })?;
+            operands.push(number);
+        }
+        // the leftmost problem has no column of spaces after it
+        groups.push(operands);
+
+        if groups.len() != operators.len() {
+            return Err(InputError::ProblemCountMismatch {
+                expected: operators.len(),
+                found: groups.len(),
+            });
+        }
+        // the rightmost problem is read first, so it takes the last operator
+        let problems = groups
+            .into_iter()
+            .zip(operators.iter().enumerate().rev())
+            .map(|(operands, (i, text))| {
+                Ok(Problem {
+                    operands,
+                    operator: Operator::parse(i + 1, text)?,
+                })
+            })
+            .collect::<Result<_, InputError>>()?;
+
+        Ok(Worksheet { problems })
+    }
+
+    fn grand_total(&self) -> i128 {
+        self.problems
+            .iter()
+            .try_fold(0i128, |acc, problem| acc.checked_add(problem.solve()))
+            .expect("grand total should fit in i128")
+    }
+}
+
+fn part1(lines: &[String]) -> Result<i128, InputError> {
+    Ok(Worksheet::read_rows(lines)?.grand_total())
+}
+
+fn part2(lines: &[String]) -> Result<i128, InputError> {
+    Ok(Worksheet::read_columns(lines)?.grand_total())
 }
 
 pub fn main() {
     let test_lines = crate::utils::read_lines("input_test/day06.txt");
     let input_lines = crate::utils::read_lines("input/day06.txt");
 
-    assert_eq!(part1(&test_lines), 4277556);
-    println!("Day 6 part 1 answer: {}", part1(&input_lines));
+    let rows = Worksheet::read_rows(&test_lines).unwrap();
+    assert_eq!(
+        rows.problems[0],
+        Problem {
+            operands: vec![123, 45, 6],
+            operator: Operator::Multiply
+        }
+    );
+    let columns = Worksheet::read_columns(&test_lines).unwrap();
+    assert_eq!(
+        columns.problems[0],
+        Problem {
+            operands: vec![4, 431, 623],
+            operator: Operator::Add
+        }
+    );
+    assert_eq!(columns.problems.len(), 4);
+
+    // operands larger than i32, and a product larger than u64
+    let large = lines_from(&["18446744073709551615 5", "3 7", "*  +"]);
+    assert_eq!(part1(&large).unwrap(), u64::MAX as i128 * 3 + 12);
+
+    assert!(matches!(
+        part1(&lines_from(&["1 2", "3 4", "+ -"])),
+        Err(InputError::UnknownOperator { problem: 2, .. })
+    ));
+    assert!(matches!(
+        part2(&lines_from(&["1 2", "3 4", "+ /"])),
+        Err(InputError::UnknownOperator { problem: 2, .. })
+    ));
+    assert!(matches!(
+        part1(&lines_from(&["1 2", "3", "+ *"])),
+        Err(InputError::OperandCountMismatch { line: 2, .. })
+    ));
+    assert!(matches!(
+        part2(&lines_from(&["1 2", "3 4", "+"])),
+        Err(InputError::ProblemCountMismatch {
+            expected: 1,
+            found: 2
+        })
+    ));
+    assert!(matches!(part1(&[]), Err(InputError::MissingOperators)));
+
+    // lines with their trailing spaces stripped read the same as padded lines
+    let ragged = test_lines
+        .iter()
+        .map(|l| l.trim_end().to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(Worksheet::read_columns(&ragged).unwrap(), columns);
+    assert_eq!(
+        part2(&lines_from(&["1", "23 4", "+  *"])).unwrap(),
+        (12 + 3) + 4
+    );
+
+    assert_eq!(part1(&test_lines).unwrap(), 4277556);
+    println!("Day 6 part 1 answer: {}", part1(&input_lines).unwrap());
 
-    assert_eq!(part2(&test_lines), 3263827);
-    println!("Day 6 part 2 answer: {}", part2(&input_lines));
+    assert_eq!(part2(&test_lines).unwrap(), 3263827);
+    println!("Day 6 part 2 answer: {}", part2(&input_lines).unwrap());
 }
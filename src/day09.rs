@@ -1,34 +1,213 @@
 use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
 
-use geo::{Contains, Coord, LineString, Polygon, Rect};
 use itertools::Itertools;
 
-fn line_to_coord(line: &str) -> Coord {
-    let parts = line.split(',').collect::<Vec<_>>();
-    Coord {
-        x: parts[0].parse().unwrap(),
-        y: parts[1].parse().unwrap(),
+use crate::utils::args::Args;
+use crate::utils::polygon::IntPolygon;
+
+type Tile = (i64, i64);
+
+/// Problems with the input that keep it from being a simple rectilinear polygon
+#[derive(Debug)]
+enum InputError {
+    Parse {
+        line: usize,
+        text: String,
+    },
+    TooFewTiles(usize),
+    DuplicateTile {
+        line: usize,
+        first_line: usize,
+    },
+    DiagonalEdge {
+        from_line: usize,
+        to_line: usize,
+    },
+    UnclosedLoop {
+        last_line: usize,
+        first_line: usize,
+    },
+    CrossingEdges {
+        edge1: (usize, usize),
+        edge2: (usize, usize),
+    },
+}
+
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InputError::Parse { line, text } => {
+                write!(f, "line {line}: cannot parse tile from '{text}'")
+            }
+            InputError::TooFewTiles(count) => {
+                write!(f, "a polygon needs at least 4 red tiles, found {count}")
+            }
+            InputError::DuplicateTile { line, first_line } => {
+                write!(f, "line {line}: tile already listed on line {first_line}")
+            }
+            InputError::DiagonalEdge { from_line, to_line } => write!(
+                f,
+                "lines {from_line} and {to_line}: tiles do not share a row or column"
+            ),
+            InputError::UnclosedLoop {
+                last_line,
+                first_line,
+            } => write!(
+                f,
+                "lines {last_line} and {first_line}: loop does not close along a row or column"
+            ),
+            InputError::CrossingEdges { edge1, edge2 } => write!(
+                f,
+                "edge from line {} to {} touches edge from line {} to {}",
+                edge1.0, edge1.1, edge2.0, edge2.1
+            ),
+        }
     }
 }
 
-fn rect_area(p1: &Coord, p2: &Coord) -> usize {
-    let width = (p1.x - p2.x).abs() as usize + 1;
-    let height = (p1.y - p2.y).abs() as usize + 1;
+fn line_to_tile(line: &str) -> Option<Tile> {
+    let (x, y) = line.split_once(',')?;
+    Some((x.trim().parse().ok()?, y.trim().parse().ok()?))
+}
+
+/// Check if two axis-aligned edges share at least one tile
+/// For axis-aligned edges this is the same as overlapping bounding boxes
+fn edges_touch(a: (Tile, Tile), b: (Tile, Tile)) -> bool {
+    a.0.0.min(a.1.0) <= b.0.0.max(b.1.0)
+        && b.0.0.min(b.1.0) <= a.0.0.max(a.1.0)
+        && a.0.1.min(a.1.1) <= b.0.1.max(b.1.1)
+        && b.0.1.min(b.1.1) <= a.0.1.max(a.1.1)
+}
+
+/// Parse the red tiles and check that they form a simple rectilinear polygon:
+/// consecutive tiles share a row or column, the loop closes and edges do not cross
+fn parse_polygon(lines: &[String]) -> Result<Vec<Tile>, Vec<InputError>> {
+    let mut errors = Vec::new();
+    let mut tiles: Vec<Tile> = Vec::new();
+    let mut line_numbers: Vec<usize> = Vec::new();
+    let mut first_seen: HashMap<Tile, usize> = HashMap::new();
+
+    for (i, line) in lines.iter().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let Some(tile) = line_to_tile(line) else {
+            errors.push(InputError::Parse {
+                line: i + 1,
+                text: line.clone(),
+            });
+            continue;
+        };
+
+        if let Some(&first_line) = first_seen.get(&tile) {
+            errors.push(InputError::DuplicateTile {
+                line: i + 1,
+                first_line,
+            });
+            continue;
+        }
+
+        first_seen.insert(tile, i + 1);
+        tiles.push(tile);
+        line_numbers.push(i + 1);
+    }
+
+    if !errors.is_empty() {
+        return Err(errors); // edges are meaningless with tiles missing
+    }
+    if tiles.len() < 4 {
+        return Err(vec![InputError::TooFewTiles(tiles.len())]);
+    }
+
+    let n = tiles.len();
+    let edge = |i: usize| (tiles[i], tiles[(i + 1) % n]);
+    let edge_lines = |i: usize| (line_numbers[i], line_numbers[(i + 1) % n]);
+
+    for i in 0..n {
+        let (a, b) = edge(i);
+        if a.0 != b.0 && a.1 != b.1 {
+            let (from_line, to_line) = edge_lines(i);
+            errors.push(if i == n - 1 {
+                InputError::UnclosedLoop {
+                    last_line: from_line,
+                    first_line: to_line,
+                }
+            } else {
+                InputError::DiagonalEdge { from_line, to_line }
+            });
+        }
+    }
+    if !errors.is_empty() {
+        return Err(errors); // the crossing check assumes axis-aligned edges
+    }
+
+    for i in 0..n {
+        // the next edge shares a vertex, so it only crosses when it doubles back over this one
+        let (a, b) = edge(i);
+        let (_, c) = edge((i + 1) % n);
+        let reverses_x = (b.0 - a.0).signum() * (c.0 - b.0).signum() < 0;
+        let reverses_y = (b.1 - a.1).signum() * (c.1 - b.1).signum() < 0;
+        if reverses_x || reverses_y {
+            errors.push(InputError::CrossingEdges {
+                edge1: edge_lines(i),
+                edge2: edge_lines((i + 1) % n),
+            });
+        }
+
+        for j in i + 2..n {
+            if i == 0 && j == n - 1 {
+                continue; // first and last edge share the first vertex
+            }
+            if edges_touch(edge(i), edge(j)) {
+                errors.push(InputError::CrossingEdges {
+                    edge1: edge_lines(i),
+                    edge2: edge_lines(j),
+                });
+            }
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(tiles)
+    } else {
+        Err(errors)
+    }
+}
+
+/// Read the red tiles, panicking with every problem found if the input is not a valid polygon
+fn read_tiles(lines: &[String]) -> Vec<Tile> {
+    parse_polygon(lines)
+        .unwrap_or_else(|errors| panic!("Invalid day 9 input:\n{}", errors.iter().join("\n")))
+}
+
+fn rect_area(p1: &Tile, p2: &Tile) -> i64 {
+    let width = (p1.0 - p2.0).abs() + 1;
+    let height = (p1.1 - p2.1).abs() + 1;
     width * height
 }
 
-fn part1(lines: &[String]) -> usize {
-    let points: Vec<Coord> = lines.iter().map(|l| line_to_coord(l)).collect();
+/// Largest rectangle spanned by any two red tiles
+fn largest_rect(points: &[Tile]) -> (Tile, Tile) {
     points
         .iter()
         .combinations(2)
-        .map(|pair| rect_area(pair[0], pair[1]))
-        .max()
+        .max_by_key(|pair| rect_area(pair[0], pair[1]))
+        .map(|pair| (*pair[0], *pair[1]))
         .unwrap()
 }
 
-fn ordered_pairs(points: &[Coord]) -> Vec<(Coord, Coord)> {
-    let mut output: Vec<(Coord, Coord)> = Vec::new();
+fn part1(lines: &[String]) -> i64 {
+    let points: Vec<Tile> = read_tiles(lines);
+    let (p1, p2) = largest_rect(&points);
+    rect_area(&p1, &p2)
+}
+
+fn ordered_pairs(points: &[Tile]) -> Vec<(Tile, Tile)> {
+    let mut output: Vec<(Tile, Tile)> = Vec::new();
 
     for i in 0..points.len() {
         for j in i + 1..points.len() {
@@ -42,43 +221,259 @@ fn ordered_pairs(points: &[Coord]) -> Vec<(Coord, Coord)> {
     output
 }
 
-// This solution works but is relatively slow
-// Ideas to optimize:
-// 1) this checks ALL pairs, how about first ordering pairs on rect size then sort descending? first that fits is answer
-// 2) nested index loops is cheaper than itertools combinations (second one allocates vecs on the heap)
-fn part2(lines: &[String]) -> usize {
-    let points: Vec<Coord> = lines.iter().map(|l| line_to_coord(l)).collect();
-    let polygon = Polygon::new(LineString::from(points.clone()), vec![]);
-
-    // this is a test but it might be even slower >_<
-    // although it is faster if you run a release build
-    let ordered_pairs = ordered_pairs(&points);
-    for pair in ordered_pairs {
-        let rect = Rect::new(pair.0, pair.1);
-        if polygon.contains(&rect) {
-            return rect_area(&pair.0, &pair.1);
+/// Compress coordinates into every distinct value plus a single representative for each gap in between
+/// All tiles within a gap behave the same, as polygon edges only run along vertex coordinates
+fn compress(values: impl Iterator<Item = i64>) -> Vec<i64> {
+    let distinct: Vec<i64> = values.sorted().dedup().collect();
+    let mut output = Vec::new();
+    for (i, &v) in distinct.iter().enumerate() {
+        output.push(v);
+        if i + 1 < distinct.len() && distinct[i + 1] > v + 1 {
+            output.push(v + 1);
         }
     }
+    output
+}
 
-    0 // default loop exhaustion
+/// Lookup table on the compressed grid to count tiles in a rectangle that are neither red nor green
+struct ContainmentTable {
+    xs: Vec<i64>,
+    ys: Vec<i64>,
+    /// prefix sums of tiles outside the polygon, with an extra leading row and column of zeros
+    outside: Vec<Vec<i64>>,
+}
 
-    // points
-    //     .iter()
-    //     .combinations(2)
-    //     .filter(|pair| {
-    //         // check if rectangle formed by pair is within polygon
-    //         let rect = Rect::new(pair[0].clone(), pair[1].clone());
-    //         polygon.contains(&rect)
-    //     })
-    //     .map(|pair| rect_area(pair[0], pair[1]))
-    //     .max()
-    //     .unwrap()
+impl ContainmentTable {
+    fn new(polygon: &IntPolygon, points: &[Tile]) -> Self {
+        let xs = compress(points.iter().map(|p| p.0));
+        let ys = compress(points.iter().map(|p| p.1));
+
+        // a compressed cell covers all tiles up to the next compressed coordinate
+        let block_size = |values: &[i64], i: usize| values.get(i + 1).map_or(1, |v| v - values[i]);
+
+        let mut outside = vec![vec![0; xs.len() + 1]; ys.len() + 1];
+        for (j, &y) in ys.iter().enumerate() {
+            for (i, &x) in xs.iter().enumerate() {
+                let cell = if polygon.contains_tile((x, y)) {
+                    0
+                } else {
+                    block_size(&xs, i) * block_size(&ys, j)
+                };
+                outside[j + 1][i + 1] =
+                    cell + outside[j][i + 1] + outside[j + 1][i] - outside[j][i];
+            }
+        }
+
+        ContainmentTable { xs, ys, outside }
+    }
+
+    /// Number of tiles outside the polygon in the rectangle spanned by two vertices
+    fn outside_count(&self, p1: &Tile, p2: &Tile) -> i64 {
+        let index = |values: &[i64], v: i64| values.binary_search(&v).unwrap();
+        let i1 = index(&self.xs, p1.0.min(p2.0));
+        let i2 = index(&self.xs, p1.0.max(p2.0));
+        let j1 = index(&self.ys, p1.1.min(p2.1));
+        let j2 = index(&self.ys, p1.1.max(p2.1));
+
+        self.outside[j2 + 1][i2 + 1] - self.outside[j1][i2 + 1] - self.outside[j2 + 1][i1]
+            + self.outside[j1][i1]
+    }
+
+    fn contains_rect(&self, p1: &Tile, p2: &Tile) -> bool {
+        self.outside_count(p1, p2) == 0
+    }
+}
+
+/// A rectangle that was larger than the answer to part 2 but did not fit in the polygon
+struct Rejection {
+    corners: (Tile, Tile),
+    reason: String,
+}
+
+/// Find the largest rectangle that only holds red or green tiles, along with the
+/// first `max_rejections` larger candidates that were rejected on the way
+fn largest_contained_rect(
+    points: &[Tile],
+    max_rejections: usize,
+) -> (Option<(Tile, Tile)>, Vec<Rejection>) {
+    let polygon = IntPolygon::new(points.to_vec());
+    let table = ContainmentTable::new(&polygon, points);
+    let mut rejections = Vec::new();
+
+    // check pairs from largest to smallest area, the first rectangle that fits is the answer
+    for (p1, p2) in ordered_pairs(points) {
+        if table.contains_rect(&p1, &p2) {
+            return (Some((p1, p2)), rejections);
+        }
+
+        if rejections.len() < max_rejections {
+            let reason = format!(
+                "{} of {} tiles are outside the polygon",
+                table.outside_count(&p1, &p2),
+                rect_area(&p1, &p2)
+            );
+            rejections.push(Rejection {
+                corners: (p1, p2),
+                reason,
+            });
+        }
+    }
+
+    (None, rejections) // default loop exhaustion
+}
+
+// Containment is checked per tile on a compressed grid, so there is no floating point involved
+fn part2(lines: &[String]) -> i64 {
+    let points: Vec<Tile> = read_tiles(lines);
+    match largest_contained_rect(&points, 0) {
+        (Some((p1, p2)), _) => rect_area(&p1, &p2),
+        (None, _) => 0,
+    }
+}
+
+const POLYGON_STYLE: &str =
+    "fill=\"#c8e6c9\" stroke=\"#2e7d32\" stroke-width=\"1\" vector-effect=\"non-scaling-stroke\"";
+const REJECTED_STYLE: &str = "fill=\"none\" stroke=\"#c62828\" stroke-width=\"1\" stroke-dasharray=\"4 4\" vector-effect=\"non-scaling-stroke\"";
+const PART1_STYLE: &str =
+    "fill=\"none\" stroke=\"#1565c0\" stroke-width=\"2\" vector-effect=\"non-scaling-stroke\"";
+const PART2_STYLE: &str = "fill=\"#ffd54f\" fill-opacity=\"0.5\" stroke=\"#ff8f00\" stroke-width=\"2\" vector-effect=\"non-scaling-stroke\"";
+
+/// SVG rectangle element covering all tiles between two corners
+fn svg_rect(p1: &Tile, p2: &Tile, style: &str, title: &str) -> String {
+    let (x, y) = (p1.0.min(p2.0), p1.1.min(p2.1));
+    let (width, height) = ((p1.0 - p2.0).abs() + 1, (p1.1 - p2.1).abs() + 1);
+    format!(
+        "  <rect x=\"{}\" y=\"{}\" width=\"{width}\" height=\"{height}\" {style}><title>{title}</title></rect>\n",
+        x as f64 - 0.5,
+        y as f64 - 0.5,
+    )
+}
+
+/// Draw the polygon through the red tiles, the answers to both parts and any rejected candidates
+fn render_svg(
+    points: &[Tile],
+    part1_rect: (Tile, Tile),
+    part2_rect: Option<(Tile, Tile)>,
+    rejections: &[Rejection],
+) -> String {
+    let (min_x, max_x) = points.iter().map(|p| p.0).minmax().into_option().unwrap();
+    let (min_y, max_y) = points.iter().map(|p| p.1).minmax().into_option().unwrap();
+    let margin = ((max_x - min_x).max(max_y - min_y) / 50).max(1);
+    let dot_radius = margin as f64 / 4.0;
+
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"{} {} {} {}\">\n",
+        min_x - margin,
+        min_y - margin,
+        max_x - min_x + 2 * margin,
+        max_y - min_y + 2 * margin
+    );
+
+    let polygon_points = points.iter().map(|p| format!("{},{}", p.0, p.1)).join(" ");
+    svg += &format!("  <polygon points=\"{polygon_points}\" {POLYGON_STYLE}/>\n");
+
+    for rejection in rejections {
+        let (p1, p2) = rejection.corners;
+        let title = format!("Rejected: {}", rejection.reason);
+        svg += &svg_rect(&p1, &p2, REJECTED_STYLE, &title);
+    }
+
+    let (p1, p2) = part1_rect;
+    let title = format!("Part 1: area {}", rect_area(&p1, &p2));
+    svg += &svg_rect(&p1, &p2, PART1_STYLE, &title);
+
+    if let Some((p1, p2)) = part2_rect {
+        let title = format!("Part 2: area {}", rect_area(&p1, &p2));
+        svg += &svg_rect(&p1, &p2, PART2_STYLE, &title);
+    }
+
+    for p in points {
+        svg += &format!(
+            "  <circle cx=\"{}\" cy=\"{}\" r=\"{dot_radius}\" fill=\"#d32f2f\"/>\n",
+            p.0, p.1
+        );
+    }
+
+    svg += "</svg>\n";
+    svg
+}
+
+/// Write an SVG of the input to the given path, usage:
+/// `day09 render [output.svg] [--input=input/day09.txt] [--rejected=0]`
+fn render(args: &Args) {
+    let output_path = args.positional(1).unwrap_or("day09.svg");
+    let input_path = args.value("input").unwrap_or("input/day09.txt");
+    let max_rejections: usize = args.parsed_or("rejected", 0);
+
+    let lines = crate::utils::read_lines(input_path);
+    let points: Vec<Tile> = read_tiles(&lines);
+    let part1_rect = largest_rect(&points);
+    let (part2_rect, rejections) = largest_contained_rect(&points, max_rejections);
+
+    for rejection in &rejections {
+        let (p1, p2) = rejection.corners;
+        println!("Rejected {p1:?} - {p2:?}: {}", rejection.reason);
+    }
+
+    let svg = render_svg(&points, part1_rect, part2_rect, &rejections);
+    fs::write(output_path, svg).expect("should be able to write file");
+    println!("Day 9 rendered to {output_path}");
+}
+
+pub fn cli(args: &[String]) {
+    let args = Args::parse(args);
+    match args.positional(0) {
+        None => main(),
+        Some("render") => render(&args),
+        Some(command) => panic!("Unknown day 9 command: {command}"),
+    }
 }
 
 pub fn main() {
     let test_lines = crate::utils::read_lines("input_test/day09.txt");
     let input_lines = crate::utils::read_lines("input/day09.txt");
 
+    let test_polygon = IntPolygon::new(read_tiles(&test_lines));
+    assert_eq!(test_polygon.double_area(), 60);
+    assert_eq!(test_polygon.boundary_count(), 30);
+    assert_eq!(test_polygon.interior_count(), 16);
+    assert!(test_polygon.contains_tile((8, 4)));
+    assert!(test_polygon.contains_tile((2, 4)));
+    assert!(!test_polygon.contains_tile((3, 2)));
+
+    let invalid = |tiles: &[&str]| parse_polygon(&crate::utils::lines_from(tiles)).unwrap_err();
+    assert!(matches!(
+        invalid(&["0,0", "4,0", "4,4", "2,4", "2,-2", "0,-2"])[..],
+        [InputError::CrossingEdges {
+            edge1: (1, 2),
+            edge2: (4, 5)
+        }]
+    ));
+    assert!(matches!(
+        invalid(&["0,0", "4,0", "4,4", "1,2"])[..],
+        [
+            InputError::DiagonalEdge {
+                from_line: 3,
+                to_line: 4
+            },
+            InputError::UnclosedLoop {
+                last_line: 4,
+                first_line: 1
+            }
+        ]
+    ));
+    assert!(matches!(
+        invalid(&["0,0", "4,0", "4,4", "4,0", "0,4"])[..],
+        [InputError::DuplicateTile {
+            line: 4,
+            first_line: 2
+        }]
+    ));
+    assert!(matches!(
+        invalid(&["0,0", "4,0", "four,4", "0,4"])[..],
+        [InputError::Parse { line: 3, .. }]
+    ));
+
     assert_eq!(part1(&test_lines), 50);
     println!("Day 9 part 1 answer: {}", part1(&input_lines));
 
@@ -1,87 +1,776 @@
-type Present = Vec<Vec<u8>>;
-type PresentColl = Vec<Present>;
-type Regions = Vec<String>;
+use std::fmt;
+use std::fs;
 
-fn parse_input(lines: &[String]) -> (PresentColl, Regions) {
-    let mut presents: PresentColl = Vec::new();
+use crate::utils::args::Args;
 
-    let mut regions_index_start = 0;
-    let mut present = Present::new();
-    for (i, line) in lines.iter().enumerate() {
-        if line.len() == 2 {
-            continue; // do not process region header lines here
+type Cells = Vec<(i32, i32)>;
+
+/// A present shape as the set of its filled cells, shifted so its bounding box starts at (0, 0)
+/// Cells are kept in reading order, so equal shapes compare equal
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct Shape {
+    cells: Cells,
+}
+
+impl Shape {
+    fn from_cells(cells: Cells) -> Self {
+        let min_x = cells.iter().map(|c| c.0).min().unwrap_or(0);
+        let min_y = cells.iter().map(|c| c.1).min().unwrap_or(0);
+        let mut cells: Cells = cells.iter().map(|&(x, y)| (x - min_x, y - min_y)).collect();
+        cells.sort_by_key(|&(x, y)| (y, x));
+        Shape { cells }
+    }
+
+    /// Parse rows in the block format, where '#' is part of the shape and '.' is not
+    fn from_rows(rows: &[String]) -> Self {
+        let mut cells: Cells = Vec::new();
+        for (y, row) in rows.iter().enumerate() {
+            for (x, ch) in row.chars().enumerate() {
+                if ch == '#' {
+                    cells.push((x as i32, y as i32));
+                }
+            }
+        }
+        Shape::from_cells(cells)
+    }
+
+    fn area(&self) -> usize {
+        self.cells.len()
+    }
+
+    /// Width and height of the smallest rectangle containing the shape
+    fn bounding_box(&self) -> (usize, usize) {
+        let width = self.cells.iter().map(|c| c.0 + 1).max().unwrap_or(0);
+        let height = self.cells.iter().map(|c| c.1 + 1).max().unwrap_or(0);
+        (width as usize, height as usize)
+    }
+
+    /// Rotate a quarter turn clockwise
+    fn rotate(&self) -> Shape {
+        Shape::from_cells(self.cells.iter().map(|&(x, y)| (-y, x)).collect())
+    }
+
+    /// Mirror left to right
+    fn flip(&self) -> Shape {
+        Shape::from_cells(self.cells.iter().map(|&(x, y)| (-x, y)).collect())
+    }
+
+    /// All distinct orientations, combining 4 rotations with an optional flip
+    fn orientations(&self) -> Vec<Shape> {
+        let mut result: Vec<Shape> = Vec::new();
+        let mut current = self.clone();
+        for _ in 0..4 {
+            for candidate in [current.clone(), current.flip()] {
+                if !result.contains(&candidate) {
+                    result.push(candidate);
+                }
+            }
+            current = current.rotate();
+        }
+        result
+    }
+
+    /// Mirror along the diagonal, swapping rows and columns
+    fn transpose(&self) -> Shape {
+        Shape::from_cells(self.cells.iter().map(|&(x, y)| (y, x)).collect())
+    }
+
+    /// The smallest of all orientations, equal for shapes that are rotations or mirrors of each other
+    fn canonical(&self) -> Shape {
+        self.orientations().into_iter().min().unwrap()
+    }
+
+    /// Cells relative to the first one in reading order, so that placing a present means
+    /// putting its first cell on the first free cell of a region
+    fn anchored_cells(&self) -> Cells {
+        let (ax, ay) = self.cells[0];
+        self.cells.iter().map(|&(x, y)| (x - ax, y - ay)).collect()
+    }
+}
+
+impl fmt::Display for Shape {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (width, height) = self.bounding_box();
+        for y in 0..height as i32 {
+            let row: String = (0..width as i32)
+                .map(|x| {
+                    if self.cells.contains(&(x, y)) {
+                        '#'
+                    } else {
+                        '.'
+                    }
+                })
+                .collect();
+            writeln!(f, "{row}")?;
         }
+        Ok(())
+    }
+}
+
+/// A region under a tree, with the number of presents of each shape that should fit in it
+struct Region {
+    width: usize,
+    height: usize,
+    counts: Vec<usize>,
+}
+
+/// Problems found while parsing the input
+#[derive(Debug)]
+enum InputError {
+    MalformedLine {
+        line: usize,
+        text: String,
+    },
+    EmptyShape {
+        line: usize,
+        index: usize,
+    },
+    DuplicateShape {
+        line: usize,
+        index: usize,
+    },
+    MissingShape {
+        line: usize,
+        index: usize,
+    },
+    NoShapes,
+    CountMismatch {
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+}
+
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InputError::MalformedLine { line, text } => {
+                write!(f, "line {line}: cannot parse '{text}'")
+            }
+            InputError::EmptyShape { line, index } => {
+                write!(f, "line {line}: shape {index} has no cells")
+            }
+            InputError::DuplicateShape { line, index } => {
+                write!(f, "line {line}: shape {index} is declared twice")
+            }
+            InputError::MissingShape { line, index } => {
+                write!(
+                    f,
+                    "line {line}: shape {index} should be declared before this one"
+                )
+            }
+            InputError::NoShapes => write!(f, "input declares no shapes"),
+            InputError::CountMismatch {
+                line,
+                expected,
+                found,
+            } => write!(
+                f,
+                "line {line}: region lists {found} present counts, but there are {expected} shapes"
+            ),
+        }
+    }
+}
+
+/// Parse a region line like `12x5: 1 0 1 0 2 2`
+fn parse_region(line: &str) -> Option<Region> {
+    let (size, counts) = line.split_once(':')?;
+    let (width, height) = size.trim().split_once('x')?;
+    let counts = counts
+        .split_whitespace()
+        .map(|c| c.parse().ok())
+        .collect::<Option<Vec<usize>>>()?;
+    Some(Region {
+        width: width.parse().ok()?,
+        height: height.parse().ok()?,
+        counts,
+    })
+}
 
-        if line.contains("x") {
-            regions_index_start = i;
-            break;
+/// Parse the shapes, indexed by the number in their header, followed by the regions
+fn parse_input(lines: &[String]) -> Result<(Vec<Shape>, Vec<Region>), InputError> {
+    let mut shapes: Vec<Shape> = Vec::new();
+    let mut regions: Vec<(usize, Region)> = Vec::new();
+
+    // shape currently being read: its index, header line number and rows so far
+    let mut current: Option<(usize, usize, Vec<String>)> = None;
+    // headers are checked to be in order, so a finished shape always goes at the end
+    let finish_shape = |shapes: &mut Vec<Shape>, current: Option<(usize, usize, Vec<String>)>| {
+        let Some((index, line, rows)) = current else {
+            return Ok(());
+        };
+        // a shape of only '.' rows has no cells to place, just like a shape without rows
+        let shape = Shape::from_rows(&rows);
+        if shape.area() == 0 {
+            return Err(InputError::EmptyShape { line, index });
         }
+        shapes.push(shape);
+        Ok(())
+    };
+
+    for (i, line) in lines.iter().enumerate() {
+        let line = line.trim();
+        let malformed = || InputError::MalformedLine {
+            line: i + 1,
+            text: line.to_string(),
+        };
 
         if line.is_empty() {
-            presents.push(present);
-            present = Present::new();
-            continue;
+            finish_shape(&mut shapes, current.take())?;
+        } else if line.contains('x') {
+            finish_shape(&mut shapes, current.take())?;
+            regions.push((i + 1, parse_region(line).ok_or_else(malformed)?));
+        } else if let Some(header) = line.strip_suffix(':') {
+            finish_shape(&mut shapes, current.take())?;
+            let index = header.parse().map_err(|_| malformed())?;
+            if index < shapes.len() {
+                return Err(InputError::DuplicateShape { line: i + 1, index });
+            }
+            if index > shapes.len() {
+                return Err(InputError::MissingShape {
+                    line: i + 1,
+                    index: shapes.len(),
+                });
+            }
+            current = Some((index, i + 1, Vec::new()));
+        } else if let Some((_, _, rows)) = current.as_mut()
+            && line.chars().all(|c| c == '#' || c == '.')
+        {
+            rows.push(line.to_string());
+        } else {
+            return Err(malformed());
         }
+    }
+    finish_shape(&mut shapes, current.take())?;
 
-        let row: Vec<u8> = line
-            .chars()
-            .map(|c| if c == '#' { 1 } else { 0 } as u8)
-            .collect();
-        present.push(row);
+    if shapes.is_empty() {
+        return Err(InputError::NoShapes);
     }
 
-    let regions = lines[regions_index_start..].to_vec();
+    for (line, region) in &regions {
+        if region.counts.len() != shapes.len() {
+            return Err(InputError::CountMismatch {
+                line: *line,
+                expected: shapes.len(),
+                found: region.counts.len(),
+            });
+        }
+    }
 
-    (presents, regions)
+    Ok((shapes, regions.into_iter().map(|(_, r)| r).collect()))
 }
 
-fn part1(input_lines: &[String]) -> usize {
-    let (presents, regions) = parse_input(input_lines);
-    let present_sizes: Vec<usize> = presents
+/// Where a present ended up in a region: its shape, which of the shape's orientations,
+/// and the position of the top left corner of that orientation's bounding box
+#[derive(Clone, Copy, Debug)]
+struct Placement {
+    shape: usize,
+    orientation: usize,
+    x: usize,
+    y: usize,
+}
+
+/// Region cells covered by a placed present
+fn placed_cells(orientations: &[Vec<Shape>], placement: &Placement) -> Cells {
+    orientations[placement.shape][placement.orientation]
+        .cells
         .iter()
-        .map(|p| p.iter().flatten().filter(|x| **x == 1).count())
-        .collect();
+        .map(|&(x, y)| (x + placement.x as i32, y + placement.y as i32))
+        .collect()
+}
 
-    let mut valid_region_count = 0;
+/// Backtracking search that fills a region cell by cell in reading order
+/// Every free cell either gets covered by the first cell of some present orientation,
+/// or is left empty, which is only allowed while the spare area (slack) lasts
+/// Identical presents are tracked as counts per shape, so their order is never searched
+struct Packer {
+    width: usize,
+    height: usize,
+    transposed: bool,
+    occupied: Vec<bool>,
+    /// per shape and orientation: cells relative to the anchor, and the anchor within the shape
+    pieces: Vec<Vec<(Cells, (i32, i32))>>,
+    remaining: Vec<usize>,
+    slack: usize,
+    placements: Vec<Placement>,
+}
 
-    for region in regions {
-        let region_parts: Vec<_> = region.split(": ").collect();
-        let size_parts: Vec<usize> = region_parts[0]
-            .split("x")
-            .map(|s| s.parse::<usize>().unwrap())
-            .collect();
-        let present_counts: Vec<usize> = region_parts[1]
-            .split(" ")
-            .map(|s| s.parse::<usize>().unwrap())
+impl Packer {
+    fn new(region: &Region, orientations: &[Vec<Shape>]) -> Self {
+        let present_area: usize = region
+            .counts
+            .iter()
+            .enumerate()
+            .map(|(i, c)| orientations[i][0].area() * c)
+            .sum();
+
+        // presents can be rotated, so transposing the region is fine, and filling along the
+        // short side keeps the band of partly filled rows narrow, which prunes much earlier
+        let transposed = region.width > region.height;
+        let (width, height) = if transposed {
+            (region.height, region.width)
+        } else {
+            (region.width, region.height)
+        };
+
+        let pieces = orientations
+            .iter()
+            .map(|shape_orientations| {
+                shape_orientations
+                    .iter()
+                    .map(|o| if transposed { o.transpose() } else { o.clone() })
+                    .map(|o| (o.anchored_cells(), o.cells[0]))
+                    .collect()
+            })
             .collect();
-        let total_present_count: usize = present_counts.iter().sum();
 
-        // try fitting without packing, every present is assumed 3x3 size
-        if total_present_count <= (size_parts[0] / 3) * (size_parts[1] / 3) {
-            valid_region_count += 1;
-            continue;
+        Packer {
+            width,
+            height,
+            transposed,
+            occupied: vec![false; width * height],
+            pieces,
+            remaining: region.counts.clone(),
+            slack: (width * height).saturating_sub(present_area),
+            placements: Vec::new(),
+        }
+    }
+
+    /// Grid index of each cell when anchored at position, or None if a cell falls outside the region
+    fn cell_index(&self, position: usize, (dx, dy): (i32, i32)) -> Option<usize> {
+        let x = (position % self.width) as i32 + dx;
+        let y = (position / self.width) as i32 + dy;
+        if x < 0 || x >= self.width as i32 || y >= self.height as i32 {
+            return None;
         }
+        Some(y as usize * self.width + x as usize)
+    }
+
+    fn fits(&self, cells: &Cells, position: usize) -> bool {
+        cells.iter().all(|&cell| {
+            self.cell_index(position, cell)
+                .is_some_and(|i| !self.occupied[i])
+        })
+    }
+
+    fn set(&mut self, cells: &Cells, position: usize, value: bool) {
+        for &cell in cells {
+            let i = self.cell_index(position, cell).unwrap();
+            self.occupied[i] = value;
+        }
+    }
+
+    /// Placement of a piece anchored at position, converted back to the untransposed region
+    fn placement(&self, shape: usize, orientation: usize, position: usize) -> Placement {
+        let (ax, ay) = self.pieces[shape][orientation].1;
+        let x = (position % self.width) as i32 - ax;
+        let y = (position / self.width) as i32 - ay;
+        let (x, y) = if self.transposed { (y, x) } else { (x, y) };
+        Placement {
+            shape,
+            orientation,
+            x: x as usize,
+            y: y as usize,
+        }
+    }
 
-        // check if perfect packing would still have too little space
-        let area_size: usize = size_parts.iter().product();
-        let present_size: usize = present_counts
+    fn solve(&mut self, start: usize) -> bool {
+        if self.remaining.iter().all(|&c| c == 0) {
+            return true;
+        }
+
+        let Some(position) = (start..self.occupied.len()).find(|&i| !self.occupied[i]) else {
+            return false; // region is full but presents remain
+        };
+
+        for shape in 0..self.pieces.len() {
+            if self.remaining[shape] == 0 {
+                continue;
+            }
+            for orientation in 0..self.pieces[shape].len() {
+                let cells = self.pieces[shape][orientation].0.clone();
+                if !self.fits(&cells, position) {
+                    continue;
+                }
+
+                self.set(&cells, position, true);
+                self.remaining[shape] -= 1;
+                let placement = self.placement(shape, orientation, position);
+                self.placements.push(placement);
+                if self.solve(position + 1) {
+                    return true;
+                }
+                self.placements.pop();
+                self.remaining[shape] += 1;
+                self.set(&cells, position, false);
+            }
+        }
+
+        // leave this cell empty, if there is space to spare
+        if self.slack > 0 {
+            self.slack -= 1;
+            self.occupied[position] = true;
+            if self.solve(position + 1) {
+                return true;
+            }
+            self.occupied[position] = false;
+            self.slack += 1;
+        }
+
+        false
+    }
+}
+
+/// Decide if all presents fit in the region, and if so, where they go
+fn pack_region(region: &Region, orientations: &[Vec<Shape>]) -> Option<Vec<Placement>> {
+    let (box_width, box_height) = orientations
+        .iter()
+        .map(|o| o[0].bounding_box())
+        .fold((0, 0), |(w, h), (bw, bh)| (w.max(bw), h.max(bh)));
+    let columns = region.width / box_width;
+    let total_present_count: usize = region.counts.iter().sum();
+
+    // try fitting without packing, every present gets a box that fits the largest one
+    if total_present_count <= columns * (region.height / box_height) {
+        let shapes = region
+            .counts
             .iter()
             .enumerate()
-            .map(|(i, pc)| present_sizes[i] * pc)
-            .sum();
-        if present_size > area_size {
+            .flat_map(|(shape, &count)| std::iter::repeat_n(shape, count));
+        let placements = shapes
+            .enumerate()
+            .map(|(i, shape)| Placement {
+                shape,
+                orientation: 0,
+                x: (i % columns) * box_width,
+                y: (i / columns) * box_height,
+            })
+            .collect();
+        return Some(placements);
+    }
+
+    // check if perfect packing would still have too little space
+    let area_size = region.width * region.height;
+    let present_size: usize = region
+        .counts
+        .iter()
+        .enumerate()
+        .map(|(i, pc)| orientations[i][0].area() * pc)
+        .sum();
+    if present_size > area_size {
+        return None;
+    }
+
+    // the real input never gets here, but the examples need actual packing
+    let mut packer = Packer::new(region, orientations);
+    packer.solve(0).then_some(packer.placements)
+}
+
+/// Reasons a set of placements is not a valid packing of a region
+#[derive(Debug)]
+enum PackingError {
+    OutOfBounds {
+        present: usize,
+        cell: (i32, i32),
+    },
+    Overlap {
+        first: usize,
+        second: usize,
+        cell: (i32, i32),
+    },
+    WrongCount {
+        shape: usize,
+        expected: usize,
+        found: usize,
+    },
+}
+
+impl fmt::Display for PackingError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PackingError::OutOfBounds { present, cell } => {
+                write!(f, "present {present} covers {cell:?}, outside the region")
+            }
+            PackingError::Overlap {
+                first,
+                second,
+                cell,
+            } => write!(f, "presents {first} and {second} both cover {cell:?}"),
+            PackingError::WrongCount {
+                shape,
+                expected,
+                found,
+            } => write!(
+                f,
+                "shape {shape} is placed {found} times instead of {expected}"
+            ),
+        }
+    }
+}
+
+/// Check independently of the solver that placements stay in bounds, do not overlap
+/// and place exactly the requested number of each shape
+fn verify_placements(
+    region: &Region,
+    orientations: &[Vec<Shape>],
+    placements: &[Placement],
+) -> Result<(), PackingError> {
+    let mut owner: Vec<Option<usize>> = vec![None; region.width * region.height];
+    let mut found = vec![0; region.counts.len()];
+
+    for (present, placement) in placements.iter().enumerate() {
+        found[placement.shape] += 1;
+        for cell in placed_cells(orientations, placement) {
+            let (x, y) = cell;
+            if x < 0 || y < 0 || x >= region.width as i32 || y >= region.height as i32 {
+                return Err(PackingError::OutOfBounds { present, cell });
+            }
+            let index = y as usize * region.width + x as usize;
+            if let Some(first) = owner[index] {
+                return Err(PackingError::Overlap {
+                    first,
+                    second: present,
+                    cell,
+                });
+            }
+            owner[index] = Some(present);
+        }
+    }
+
+    for (shape, (&expected, &found)) in region.counts.iter().zip(&found).enumerate() {
+        if expected != found {
+            return Err(PackingError::WrongCount {
+                shape,
+                expected,
+                found,
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// Letter used to draw the n-th present, cycling through upper and lower case
+fn present_letter(present: usize) -> char {
+    let letters = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+    letters[present % letters.len()] as char
+}
+
+/// Draw the region with every present in its own letter and empty cells as '.'
+fn render_ascii(region: &Region, orientations: &[Vec<Shape>], placements: &[Placement]) -> String {
+    let mut grid = vec![vec!['.'; region.width]; region.height];
+    for (present, placement) in placements.iter().enumerate() {
+        for (x, y) in placed_cells(orientations, placement) {
+            grid[y as usize][x as usize] = present_letter(present);
+        }
+    }
+    grid.iter()
+        .map(|row| row.iter().collect::<String>() + "\n")
+        .collect()
+}
+
+/// Draw the region as an SVG, with one colour per present and its letter in each cell
+fn render_svg(region: &Region, orientations: &[Vec<Shape>], placements: &[Placement]) -> String {
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"-1 -1 {} {}\">\n",
+        region.width + 2,
+        region.height + 2
+    );
+    svg += &format!(
+        "  <rect width=\"{}\" height=\"{}\" fill=\"#eeeeee\" stroke=\"#424242\" stroke-width=\"0.05\"/>\n",
+        region.width, region.height
+    );
+
+    for (present, placement) in placements.iter().enumerate() {
+        // spread hues using the golden angle so neighbouring presents get distinct colours
+        let hue = (present as f64 * 137.508) % 360.0;
+        let letter = present_letter(present);
+        svg += &format!("  <g fill=\"hsl({hue:.0}, 65%, 60%)\">\n");
+        for (x, y) in placed_cells(orientations, placement) {
+            svg += &format!(
+                "    <rect x=\"{x}\" y=\"{y}\" width=\"1\" height=\"1\" stroke=\"#ffffff\" stroke-width=\"0.05\"/>\n"
+            );
+            svg += &format!(
+                "    <text x=\"{}\" y=\"{}\" font-size=\"0.6\" text-anchor=\"middle\" fill=\"#212121\">{letter}</text>\n",
+                x as f64 + 0.5,
+                y as f64 + 0.7
+            );
+        }
+        svg += "  </g>\n";
+    }
+
+    svg += "</svg>\n";
+    svg
+}
+
+fn part1(input_lines: &[String]) -> usize {
+    let (presents, regions) =
+        parse_input(input_lines).unwrap_or_else(|e| panic!("Invalid day 12 input: {e}"));
+    let orientations: Vec<Vec<Shape>> = presents.iter().map(Shape::orientations).collect();
+
+    regions
+        .iter()
+        .filter(|region| pack_region(region, &orientations).is_some())
+        .count()
+}
+
+/// Print how the presents are packed in each region, usage:
+/// `day12 render [--input=input/day12.txt] [--region=N] [--svg=output.svg]`
+/// Writing an SVG needs a single region to be selected
+fn render(args: &Args) {
+    let input_path = args.value("input").unwrap_or("input/day12.txt");
+    let selected: Option<usize> = args.parsed("region");
+    let svg_path = args.value("svg");
+    if svg_path.is_some() && selected.is_none() {
+        panic!("--svg needs a single --region");
+    }
+
+    let lines = crate::utils::read_lines(input_path);
+    let (presents, regions) =
+        parse_input(&lines).unwrap_or_else(|e| panic!("Invalid day 12 input: {e}"));
+    let orientations: Vec<Vec<Shape>> = presents.iter().map(Shape::orientations).collect();
+    if let Some(region) = selected
+        && region >= regions.len()
+    {
+        panic!(
+            "--region {region} does not exist, there are {} regions",
+            regions.len()
+        );
+    }
+
+    for (i, region) in regions.iter().enumerate() {
+        if selected.is_some_and(|s| s != i) {
             continue;
         }
 
-        // apparently the real input is structured in such a way that this panic is never triggered
-        // this is not so for the examples, that's why we skip testing those
-        panic!("Packing is difficult");
+        println!("Region {i} ({}x{}):", region.width, region.height);
+        let Some(placements) = pack_region(region, &orientations) else {
+            println!("presents do not fit\n");
+            continue;
+        };
+        if let Err(e) = verify_placements(region, &orientations, &placements) {
+            panic!("Invalid packing for region {i}: {e}");
+        }
+        println!("{}", render_ascii(region, &orientations, &placements));
+
+        if let Some(svg_path) = svg_path {
+            let svg = render_svg(region, &orientations, &placements);
+            fs::write(svg_path, svg).expect("should be able to write file");
+            println!("Region {i} rendered to {svg_path}");
+        }
     }
+}
 
-    valid_region_count
+pub fn cli(args: &[String]) {
+    let args = Args::parse(args);
+    match args.positional(0) {
+        None => main(),
+        Some("render") => render(&args),
+        Some(command) => panic!("Unknown day 12 command: {command}"),
+    }
 }
 
 pub fn main() {
+    let test_lines = crate::utils::read_lines("input_test/day12.txt");
     let input_lines = crate::utils::read_lines("input/day12.txt");
+
+    let (test_presents, test_regions) = parse_input(&test_lines).unwrap();
+    assert_eq!(test_presents.len(), 6);
+    assert_eq!(test_regions[1].width, 12);
+    assert_eq!(test_regions[1].height, 5);
+    assert_eq!(test_regions[1].counts, vec![1, 0, 1, 0, 2, 2]);
+
+    let parse = |lines: &[&str]| parse_input(&crate::utils::lines_from(lines));
+    assert!(matches!(
+        parse(&["0:", "##", "", "1:", "#.", "", "4x4: 1 2 3"]),
+        Err(InputError::CountMismatch {
+            line: 7,
+            expected: 2,
+            found: 3
+        })
+    ));
+    assert!(matches!(
+        parse(&["0:", "##", "#?", "", "4x4: 1"]),
+        Err(InputError::MalformedLine { line: 3, .. })
+    ));
+    assert!(matches!(
+        parse(&["1:", "##", "", "4x4: 0 1"]),
+        Err(InputError::MissingShape { line: 1, index: 0 })
+    ));
+    assert!(matches!(
+        parse(&["0:", "##", "", "99999999999:", "#"]),
+        Err(InputError::MissingShape { line: 4, index: 1 })
+    ));
+    assert!(matches!(
+        parse(&["0:", "...", "", "3x3: 1"]),
+        Err(InputError::EmptyShape { line: 1, index: 0 })
+    ));
+    assert!(matches!(
+        parse(&["0:", "##", "", "0:", "#", "", "4x4: 1"]),
+        Err(InputError::DuplicateShape { line: 4, index: 0 })
+    ));
+    assert!(matches!(parse(&["4x4:"]), Err(InputError::NoShapes)));
+
+    assert_eq!(test_presents[0].to_string(), "###\n##.\n##.\n");
+    assert_eq!(test_presents[0].area(), 7);
+    assert_eq!(test_presents[4].bounding_box(), (3, 3));
+    assert_eq!(test_presents[0].orientations().len(), 8);
+    assert_eq!(test_presents[5].orientations().len(), 2);
+    // the shape as declared comes first, quick fits place presents in that orientation
+    assert_eq!(test_presents[0].orientations()[0], test_presents[0]);
+    assert_eq!(
+        test_presents[3].canonical(),
+        test_presents[3].flip().rotate().canonical()
+    );
+    assert_ne!(test_presents[0].canonical(), test_presents[1].canonical());
+
+    let test_orientations: Vec<Vec<Shape>> =
+        test_presents.iter().map(Shape::orientations).collect();
+    for region in &test_regions[..2] {
+        let placements = pack_region(region, &test_orientations).unwrap();
+        assert!(verify_placements(region, &test_orientations, &placements).is_ok());
+    }
+    let overlapping = [
+        Placement {
+            shape: 4,
+            orientation: 0,
+            x: 0,
+            y: 0,
+        },
+        Placement {
+            shape: 4,
+            orientation: 0,
+            x: 1,
+            y: 0,
+        },
+    ];
+    assert!(matches!(
+        verify_placements(&test_regions[0], &test_orientations, &overlapping),
+        Err(PackingError::Overlap {
+            first: 0,
+            second: 1,
+            ..
+        })
+    ));
+    let out_of_bounds = [
+        Placement {
+            shape: 4,
+            orientation: 0,
+            x: 0,
+            y: 0,
+        },
+        Placement {
+            shape: 4,
+            orientation: 0,
+            x: 0,
+            y: 3,
+        },
+    ];
+    assert!(matches!(
+        verify_placements(&test_regions[0], &test_orientations, &out_of_bounds),
+        Err(PackingError::OutOfBounds { present: 1, .. })
+    ));
+
+    assert_eq!(part1(&test_lines), 2);
     println!("Day 12 part 1 answer: {}", part1(&input_lines));
 }
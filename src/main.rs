@@ -13,16 +13,38 @@ mod day12;
 mod utils;
 
 fn main() {
-    day01::main();
-    day02::main();
-    day03::main();
-    day04::main();
-    day05::main();
-    day06::main();
-    day07::main();
-    day08::main();
-    day09::main();
-    day10::main();
-    day11::main();
-    day12::main();
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    // without arguments, run every day
+    let Some((day, rest)) = args.split_first() else {
+        day01::main();
+        day02::main();
+        day03::main();
+        day04::main();
+        day05::main();
+        day06::main();
+        day07::main();
+        day08::main();
+        day09::main();
+        day10::main();
+        day11::main();
+        day12::main();
+        return;
+    };
+
+    match day.as_str() {
+        "day01" => day01::cli(rest),
+        "day02" => day02::cli(rest),
+        "day03" => day03::cli(rest),
+        "day04" => day04::cli(rest),
+        "day05" => day05::cli(rest),
+        "day06" => day06::main(),
+        "day07" => day07::main(),
+        "day08" => day08::main(),
+        "day09" => day09::cli(rest),
+        "day10" => day10::main(),
+        "day11" => day11::main(),
+        "day12" => day12::cli(rest),
+        _ => panic!("Unknown day: {day}"),
+    }
 }
@@ -1,8 +1,12 @@
 use std::io::BufRead;
 use std::{fs::File, io::BufReader};
 
+pub mod args;
+pub mod automaton;
 pub mod direction;
 pub mod grid;
+pub mod intervals;
+pub mod polygon;
 
 /// Read a file path into a vector of strings, without newline characters
 pub fn read_lines(file_path: &str) -> Vec<String> {
@@ -13,3 +17,8 @@ pub fn read_lines(file_path: &str) -> Vec<String> {
         .map(|l| l.expect("should be able to parse line"))
         .collect()
 }
+
+/// Owned lines from string literals, in the same form as `read_lines` returns them
+pub fn lines_from(lines: &[&str]) -> Vec<String> {
+    lines.iter().map(|l| l.to_string()).collect()
+}
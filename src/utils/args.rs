@@ -0,0 +1,58 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+
+/// Command line arguments given after the day name
+/// Options are written as `--name=value`, everything else is a positional argument
+pub struct Args {
+    positional: Vec<String>,
+    options: HashMap<String, String>,
+}
+
+impl Args {
+    pub fn parse(args: &[String]) -> Self {
+        let mut positional = Vec::new();
+        let mut options = HashMap::new();
+
+        for arg in args {
+            match arg.strip_prefix("--") {
+                Some(option) => {
+                    let (name, value) = option.split_once('=').unwrap_or((option, ""));
+                    options.insert(name.to_string(), value.to_string());
+                }
+                None => positional.push(arg.clone()),
+            }
+        }
+
+        Args {
+            positional,
+            options,
+        }
+    }
+
+    pub fn positional(&self, index: usize) -> Option<&str> {
+        self.positional.get(index).map(|s| s.as_str())
+    }
+
+    /// Check if an option was given, with or without a value
+    pub fn flag(&self, name: &str) -> bool {
+        self.options.contains_key(name)
+    }
+
+    pub fn value(&self, name: &str) -> Option<&str> {
+        self.options.get(name).map(|s| s.as_str())
+    }
+
+    /// Parse the value of an option, or None if the option was not given
+    pub fn parsed<T: FromStr>(&self, name: &str) -> Option<T> {
+        self.value(name).map(|value| {
+            value
+                .parse()
+                .unwrap_or_else(|_| panic!("Invalid value for --{name}: {value}"))
+        })
+    }
+
+    /// Parse the value of an option, or use the default if the option was not given
+    pub fn parsed_or<T: FromStr>(&self, name: &str, default: T) -> T {
+        self.parsed(name).unwrap_or(default)
+    }
+}
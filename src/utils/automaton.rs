@@ -0,0 +1,84 @@
+use std::collections::HashMap;
+
+/// How the cells of a grid are updated within one generation
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Update {
+    /// Every cell sees the grid as it was at the start of the generation
+    Synchronous,
+    /// Cells are updated one by one in reading order, and see the changes made before them
+    Asynchronous,
+}
+
+/// Maps a cell and its neighbours to the state of the cell in the next generation
+type Rule<T> = Box<dyn Fn(T, &[T]) -> T>;
+
+/// A cellular automaton on a sparse grid, where a rule maps a cell and its neighbours to the next state
+/// Neighbours outside the grid are left out, so cells on the edge get fewer of them
+pub struct Automaton<T> {
+    neighbourhood: Vec<(i32, i32)>,
+    update: Update,
+    rule: Rule<T>,
+}
+
+impl<T: Copy + PartialEq> Automaton<T> {
+    pub fn new(
+        neighbourhood: Vec<(i32, i32)>,
+        update: Update,
+        rule: impl Fn(T, &[T]) -> T + 'static,
+    ) -> Self {
+        Automaton {
+            neighbourhood,
+            update,
+            rule: Box::new(rule),
+        }
+    }
+
+    fn next_state(&self, grid: &HashMap<(i32, i32), T>, position: (i32, i32)) -> T {
+        let (x, y) = position;
+        let neighbours: Vec<T> = self
+            .neighbourhood
+            .iter()
+            .filter_map(|&(dx, dy)| grid.get(&(x + dx, y + dy)).copied())
+            .collect();
+        (self.rule)(grid[&position], &neighbours)
+    }
+
+    /// Advance one generation, returning how many cells changed
+    pub fn step(&self, grid: &mut HashMap<(i32, i32), T>) -> usize {
+        let mut positions: Vec<(i32, i32)> = grid.keys().copied().collect();
+        positions.sort_by_key(|&(x, y)| (y, x));
+
+        match self.update {
+            Update::Synchronous => {
+                let changes: Vec<((i32, i32), T)> = positions
+                    .into_iter()
+                    .map(|position| (position, self.next_state(grid, position)))
+                    .filter(|(position, state)| grid[position] != *state)
+                    .collect();
+                let changed = changes.len();
+                grid.extend(changes);
+                changed
+            }
+            Update::Asynchronous => {
+                let mut changed = 0;
+                for position in positions {
+                    let state = self.next_state(grid, position);
+                    if grid[&position] != state {
+                        grid.insert(position, state);
+                        changed += 1;
+                    }
+                }
+                changed
+            }
+        }
+    }
+
+    /// Step until a generation changes nothing, returning the number of generations that changed something
+    pub fn run_to_fixpoint(&self, grid: &mut HashMap<(i32, i32), T>) -> usize {
+        let mut generations = 0;
+        while self.step(grid) > 0 {
+            generations += 1;
+        }
+        generations
+    }
+}
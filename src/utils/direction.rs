@@ -23,7 +23,6 @@ impl Direction {
         }
     }
 
-    #[allow(dead_code)]
     pub fn straight_directions() -> Vec<Direction> {
         vec![
             Direction::Up,
@@ -46,8 +45,3 @@ impl Direction {
         ]
     }
 }
-
-pub fn move_in_direction(start: (i32, i32), direction: &Direction, steps: i32) -> (i32, i32) {
-    let (dx, dy) = direction.value();
-    (start.0 + dx * steps, start.1 + dy * steps)
-}
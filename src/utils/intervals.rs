@@ -0,0 +1,129 @@
+/// A set of integers stored as sorted, disjoint, inclusive ranges
+/// Overlapping and adjacent ranges are merged on insert, so every range is kept as large as possible
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RangeSet {
+    ranges: Vec<(i64, i64)>,
+}
+
+impl RangeSet {
+    pub fn new() -> Self {
+        RangeSet { ranges: Vec::new() }
+    }
+
+    /// Add all values from start to end (inclusive), merging with any ranges it touches
+    /// A reversed range is swapped, so `insert(10, 3)` adds the same values as `insert(3, 10)`
+    pub fn insert(&mut self, start: i64, end: i64) {
+        let (start, end) = (start.min(end), start.max(end));
+        // first range that overlaps or is adjacent to the new one
+        let first = self
+            .ranges
+            .partition_point(|&(_, e)| e < start.saturating_sub(1));
+        // first range after that which lies entirely past the new one
+        let last =
+            first + self.ranges[first..].partition_point(|&(s, _)| s <= end.saturating_add(1));
+
+        let mut merged = (start, end);
+        if first < last {
+            merged.0 = merged.0.min(self.ranges[first].0);
+            merged.1 = merged.1.max(self.ranges[last - 1].1);
+        }
+        self.ranges.splice(first..last, [merged]);
+    }
+
+    /// Check membership with a binary search over the ranges
+    pub fn contains(&self, value: i64) -> bool {
+        let i = self.ranges.partition_point(|&(_, end)| end < value);
+        self.ranges.get(i).is_some_and(|&(start, _)| start <= value)
+    }
+
+    /// Number of integers covered by all ranges together
+    /// Uses 128 bits, as a range over all of i64 holds 2^64 values
+    pub fn covered_length(&self) -> u128 {
+        self.ranges
+            .iter()
+            .map(|&(start, end)| (end as i128 - start as i128 + 1) as u128)
+            .sum()
+    }
+
+    /// Iterate over the merged ranges in ascending order
+    pub fn iter(&self) -> impl Iterator<Item = (i64, i64)> + '_ {
+        self.ranges.iter().copied()
+    }
+
+    pub fn union(&self, other: &RangeSet) -> RangeSet {
+        let mut result = self.clone();
+        for (start, end) in other.iter() {
+            result.insert(start, end);
+        }
+        result
+    }
+
+    pub fn intersection(&self, other: &RangeSet) -> RangeSet {
+        let mut result = RangeSet::new();
+        let (mut i, mut j) = (0, 0);
+        while i < self.ranges.len() && j < other.ranges.len() {
+            let (a_start, a_end) = self.ranges[i];
+            let (b_start, b_end) = other.ranges[j];
+            let (start, end) = (a_start.max(b_start), a_end.min(b_end));
+            if start <= end {
+                result.ranges.push((start, end));
+            }
+
+            // advance whichever range ends first, the other may still overlap the next one
+            if a_end < b_end {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        result
+    }
+
+    /// All values in this set that are not in the other set
+    pub fn difference(&self, other: &RangeSet) -> RangeSet {
+        let mut result = RangeSet::new();
+        let mut j = 0;
+        for &(start, end) in &self.ranges {
+            // first value that is not handled yet, None once the whole range is cut out
+            let mut current = Some(start);
+
+            // skip ranges of the other set that end before this range starts
+            while j < other.ranges.len() && other.ranges[j].1 < start {
+                j += 1;
+            }
+
+            // cut out every range of the other set that overlaps this one
+            let mut k = j;
+            while let Some(value) = current
+                && k < other.ranges.len()
+                && other.ranges[k].0 <= end
+            {
+                let (cut_start, cut_end) = other.ranges[k];
+                if cut_start > value {
+                    result.ranges.push((value, cut_start - 1));
+                }
+                current = if cut_end >= end {
+                    None
+                } else {
+                    Some(value.max(cut_end + 1))
+                };
+                k += 1;
+            }
+
+            if let Some(value) = current {
+                result.ranges.push((value, end));
+            }
+        }
+        result
+    }
+}
+
+impl FromIterator<(i64, i64)> for RangeSet {
+    fn from_iter<I: IntoIterator<Item = (i64, i64)>>(iter: I) -> Self {
+        let mut set = RangeSet::new();
+        for (start, end) in iter {
+            set.insert(start, end);
+        }
+        set
+    }
+}
@@ -0,0 +1,83 @@
+/// A closed polygon with its vertices on the integer lattice, e.g. tile positions in a grid
+/// All calculations use exact integer arithmetic
+pub struct IntPolygon {
+    vertices: Vec<(i64, i64)>,
+}
+
+fn gcd(a: i64, b: i64) -> i64 {
+    if b == 0 { a } else { gcd(b, a % b) }
+}
+
+/// Check if point p lies on the segment from a to b (inclusive)
+fn on_segment(a: (i64, i64), b: (i64, i64), p: (i64, i64)) -> bool {
+    let cross = (b.0 - a.0) * (p.1 - a.1) - (b.1 - a.1) * (p.0 - a.0);
+    cross == 0
+        && p.0 >= a.0.min(b.0)
+        && p.0 <= a.0.max(b.0)
+        && p.1 >= a.1.min(b.1)
+        && p.1 <= a.1.max(b.1)
+}
+
+impl IntPolygon {
+    pub fn new(vertices: Vec<(i64, i64)>) -> Self {
+        assert!(vertices.len() >= 3, "a polygon needs at least 3 vertices");
+        IntPolygon { vertices }
+    }
+
+    /// Iterate over all edges, including the closing edge from the last vertex back to the first
+    pub fn edges(&self) -> impl Iterator<Item = ((i64, i64), (i64, i64))> + '_ {
+        let next = self.vertices.iter().cycle().skip(1);
+        self.vertices.iter().zip(next).map(|(a, b)| (*a, *b))
+    }
+
+    /// Twice the enclosed area using the shoelace formula
+    /// Lattice polygons can have half-integer areas, doubling keeps the result exact
+    pub fn double_area(&self) -> i64 {
+        self.edges()
+            .map(|((x1, y1), (x2, y2))| x1 * y2 - x2 * y1)
+            .sum::<i64>()
+            .abs()
+    }
+
+    /// Number of tiles on the boundary, an edge covers gcd(dx, dy) tiles excluding its start
+    pub fn boundary_count(&self) -> i64 {
+        self.edges()
+            .map(|((x1, y1), (x2, y2))| gcd((x2 - x1).abs(), (y2 - y1).abs()))
+            .sum()
+    }
+
+    /// Number of tiles strictly inside, using Pick's theorem: A = I + B/2 - 1
+    pub fn interior_count(&self) -> i64 {
+        (self.double_area() - self.boundary_count() + 2) / 2
+    }
+
+    pub fn on_boundary(&self, tile: (i64, i64)) -> bool {
+        self.edges().any(|(a, b)| on_segment(a, b, tile))
+    }
+
+    /// Check if the centre of a tile lies inside the polygon or on its boundary
+    /// Casts a ray to the right and counts edge crossings, using a half-open rule on y
+    /// so that a ray through a vertex is not counted twice
+    pub fn contains_tile(&self, tile: (i64, i64)) -> bool {
+        if self.on_boundary(tile) {
+            return true;
+        }
+
+        let (px, py) = tile;
+        let mut inside = false;
+        for ((x1, y1), (x2, y2)) in self.edges() {
+            if (y1 > py) == (y2 > py) {
+                continue; // edge does not cross the ray's height
+            }
+
+            // the crossing lies right of the tile if px < x1 + (py - y1) * (x2 - x1) / (y2 - y1)
+            // multiply out the division and flip the comparison when the edge points down
+            let lhs = (px - x1) * (y2 - y1);
+            let rhs = (py - y1) * (x2 - x1);
+            if (y2 > y1 && lhs < rhs) || (y2 < y1 && lhs > rhs) {
+                inside = !inside;
+            }
+        }
+        inside
+    }
+}